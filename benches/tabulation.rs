@@ -0,0 +1,108 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use lta::bible::{references, AllReferences, References};
+use lta::hugo::{Header, Metadata};
+use std::path::PathBuf;
+
+const POSTS: usize = 10_000;
+const REFS_PER_POST: usize = 50;
+
+const BOOKS: [&str; 8] = [
+    "Genesis", "Psalms", "Isaiah", "Matthew", "John", "Acts", "Romans", "Hebrews",
+];
+
+/// a deterministic stream of pseudo-random numbers, so every run sees the same corpus
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % n
+    }
+}
+
+/// the text of each synthetic post, citing passages mostly clustered in a few chapters
+fn corpus() -> Vec<String> {
+    let mut rng = Lcg(0x5eed);
+
+    (0..POSTS)
+        .map(|_| {
+            let book = BOOKS[rng.next(BOOKS.len())];
+            let home_chapter = 1 + rng.next(40);
+
+            (0..REFS_PER_POST)
+                .map(|_| {
+                    let book = if rng.next(4) == 0 {
+                        BOOKS[rng.next(BOOKS.len())]
+                    } else {
+                        book
+                    };
+                    let chapter = if rng.next(2) == 0 {
+                        home_chapter
+                    } else {
+                        1 + rng.next(50)
+                    };
+                    format!("{} {}:{}", book, chapter, 1 + rng.next(30))
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .collect()
+}
+
+fn posts(corpus: &[String]) -> Vec<(Metadata, References)> {
+    corpus
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let mut header = Header::new(&format!("Post {}", i), "");
+            header.date = Some(format!(
+                "20{:02}-{:02}-{:02}T06:00:00Z",
+                i / 336,
+                1 + i / 28 % 12,
+                1 + i % 28
+            ));
+            let metadata = Metadata {
+                path: PathBuf::from(format!("content/post/{}.md", i)),
+                url: format!("/post/{}.md", i),
+                header,
+            };
+            let (refs, _) = references("", text);
+
+            (metadata, refs)
+        })
+        .collect()
+}
+
+fn tabulation(c: &mut Criterion) {
+    let corpus = corpus();
+
+    let mut group = c.benchmark_group(format!("{} posts x {} refs", POSTS, REFS_PER_POST));
+    group.sample_size(10);
+
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || posts(&corpus),
+            |posts| posts.into_iter().collect::<AllReferences>(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("coalesce", |b| {
+        b.iter_batched(
+            || posts(&corpus).into_iter().collect::<AllReferences>(),
+            |mut all| {
+                all.coalesce();
+                all
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, tabulation);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
 use super::hugo::Metadata;
 use super::util::slice_cmp;
 use books::{book, is_single_chapter_book};
-use itertools::Itertools;
 use std::{
     cmp::{self, Ordering},
     collections::{BTreeMap, HashMap},
@@ -9,8 +8,8 @@ use std::{
     num::ParseIntError,
     str::FromStr,
 };
-pub use tabulation::Writer;
 use tabulation::{BookReferences, BookReferences1};
+pub use tabulation::{IndexOptions, RowOrder, SinglePage};
 
 /// integer used for chapter index
 type CInt = u8;
@@ -56,6 +55,10 @@ impl VSpan {
             Line(_, x2) => *x2,
         }
     }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.lower() <= other.upper() && other.lower() <= self.upper()
+    }
 }
 
 impl PartialOrd for VSpan {
@@ -79,11 +82,7 @@ impl Ord for VSpan {
 
 impl fmt::Display for VSpan {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        use VSpan::*;
-        match self {
-            Point(x) => write!(f, "{}", x),
-            Line(x1, x2) => write!(f, "{}-{}", x1, x2),
-        }
+        self.cite(f, Style::plain())
     }
 }
 
@@ -124,12 +123,7 @@ impl<'a> IntoIterator for &'a VSpans {
 
 impl fmt::Display for VSpans {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{}",
-            Itertools::intersperse(self.0.iter().map(|s| s.to_string()), ",\u{200A}".to_string())
-                .collect::<String>()
-        )
+        self.cite(f, Style::plain())
     }
 }
 
@@ -143,6 +137,17 @@ impl ChapterVerses {
     fn new(chapter: Option<Chapter>, verses: VSpans) -> Self {
         Self { chapter, verses }
     }
+
+    /// whether any verse is in both, where a chapter without verses is the whole chapter
+    fn overlaps(&self, other: &Self) -> bool {
+        self.chapter == other.chapter
+            && (self.verses.is_empty()
+                || other.verses.is_empty()
+                || self
+                    .verses
+                    .into_iter()
+                    .any(|s0| other.verses.into_iter().any(|s1| s0.overlaps(s1))))
+    }
 }
 
 impl PartialOrd for ChapterVerses {
@@ -165,26 +170,8 @@ impl Ord for ChapterVerses {
 }
 
 impl Display for ChapterVerses {
-    // we really do want to write out a warning to stderr here so:
-    #[allow(clippy::print_in_format_impl)]
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-        match self.chapter {
-            Some(chapter) => {
-                if self.verses.is_empty() {
-                    write!(f, "{}", chapter)
-                } else {
-                    write!(f, "{}:{}", chapter, self.verses)
-                }
-            }
-            None => {
-                if self.verses.is_empty() {
-                    eprintln!("WARNING: no chapter or verses for ChapterVerses::fmt");
-                    Ok(())
-                } else {
-                    write!(f, "v{}", self.verses)
-                }
-            }
-        }
+        self.cite(f, Style::plain())
     }
 }
 
@@ -198,60 +185,6 @@ impl ChaptersVerses {
     }
 }
 
-impl PartialOrd for ChaptersVerses {
-    /// To determine whether there is an ordering, verses are ignored,
-    /// only chapters matter, but these must be strictly in order, with no interleaving.
-    /// If an ordering is possible, the earliest verses that differ influence the order.
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        fn compatible(o1: Ordering, o2: Ordering) -> bool {
-            !matches!((o1, o2), (Less, Greater) | (Greater, Less))
-        }
-
-        fn all_compatible(o: Ordering, cvs0: &[&ChapterVerses], cvs1: &[&ChapterVerses]) -> bool {
-            cvs0.iter().all(|cv0| {
-                cvs1.iter()
-                    .all(|cv1| compatible(o, cv0.chapter.cmp(&cv1.chapter)))
-            })
-        }
-
-        let mut it0 = self.0.iter().peekable();
-        let mut it1 = other.0.iter().peekable();
-
-        use Ordering::*;
-        let mut verse_order: Option<Ordering> = None;
-
-        loop {
-            match (it0.peek(), it1.peek()) {
-                (Some(cv0), Some(cv1)) => {
-                    match cv0.chapter.cmp(&cv1.chapter) {
-                        Equal => {
-                            if verse_order.is_none() || verse_order == Some(Equal) {
-                                verse_order = Some(cv0.cmp(cv1));
-                            }
-
-                            // skip both and keep comparing
-                            it0.next();
-                            it1.next();
-                        }
-                        candidate => {
-                            let v0 = it0.collect::<Vec<&ChapterVerses>>();
-                            let v1 = it1.collect::<Vec<&ChapterVerses>>();
-                            if all_compatible(candidate, &v0, &v1) {
-                                return Some(candidate);
-                            } else {
-                                return None;
-                            }
-                        }
-                    }
-                }
-                (Some(_), None) => return Some(Greater),
-                (None, Some(_)) => return Some(Less),
-                (None, None) => return verse_order.or(Some(Equal)),
-            }
-        }
-    }
-}
-
 impl<'a> IntoIterator for &'a ChaptersVerses {
     type Item = &'a ChapterVerses;
     type IntoIter = std::slice::Iter<'a, ChapterVerses>;
@@ -272,20 +205,11 @@ impl IntoIterator for ChaptersVerses {
 
 impl Display for ChaptersVerses {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-        let separator = if f.alternate() { " <br/> " } else { "; " };
-        write!(
-            f,
-            "{}",
-            Itertools::intersperse(
-                self.0.iter().map(|cv| cv.to_string()),
-                separator.to_string()
-            )
-            .collect::<String>()
-        )
+        self.cite(f, Style::plain())
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Default, Debug)]
 pub struct References(HashMap<&'static str, ChaptersVerses>);
 
 /// consuming iterator
@@ -298,19 +222,76 @@ impl IntoIterator for References {
     }
 }
 
-#[derive(Debug)]
+#[derive(Default, Debug)]
 pub struct AllReferences {
     metadata: Vec<Metadata>,
     post_index_by_epoch: BTreeMap<i64, usize>,
     post_sequence_number_by_index: Vec<Option<usize>>,
+    quoted_translations_by_index: Vec<Vec<(String, References)>>,
     separated_refs_by_book: HashMap<&'static str, BookReferences1>,
     refs_by_book: HashMap<&'static str, BookReferences>,
 }
 
+/// posts with the given dates and text, titled and numbered in order, for tests
+#[cfg(test)]
+pub(crate) fn all_references(posts: &[(Option<&str>, &str)]) -> AllReferences {
+    posts
+        .iter()
+        .enumerate()
+        .map(|(i, (date, text))| {
+            let mut header = super::hugo::Header::new(&format!("Post {}", i), "");
+            header.date = date.map(|date| date.to_string());
+            let metadata = Metadata {
+                path: std::path::PathBuf::from(format!("{}.md", i)),
+                url: format!("/post/{}.md", i),
+                header,
+            };
+            let (refs, _) = references("", text);
+
+            (metadata, refs)
+        })
+        .collect()
+}
+
 mod books;
+mod coverage;
+pub use coverage::{BookCoverage, ChapterCoverage};
+mod cross_references;
+pub use cross_references::{write_related, CrossReferences};
+mod data;
+pub use data::ScriptureData;
 mod extraction;
-pub use extraction::references;
+pub use extraction::{quoted_translations, references};
+mod filling;
+pub use filling::with_filled_quotes;
+mod gaps;
+pub use gaps::{months_before, write_gaps, write_plan};
 mod index_links;
 pub use index_links::with_index_links;
+mod lectionary;
+pub use lectionary::Lectionary;
+mod line_file;
+mod normalization;
+pub use normalization::normalized;
+mod osis;
+mod parallels;
+pub use parallels::Parallels;
+mod pericopes;
+pub use pericopes::Pericopes;
+mod related;
+pub use related::RelatedPost;
+mod report;
+pub use report::Report;
+mod statistics;
+pub use statistics::Statistics;
+mod style;
+use style::Cite;
+pub use style::{Preset, Style, StyleConfig};
 mod tabulation;
 mod tests;
+mod text;
+pub use text::{Excerpts, TextFormat, TextStore, Texts};
+mod verification;
+pub use verification::mismatched_quotes;
+mod versification;
+pub use versification::Scheme;
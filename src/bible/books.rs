@@ -1,3 +1,4 @@
+use super::VInt;
 use lazy_static::lazy_static;
 use std::collections::{HashMap, HashSet};
 
@@ -21,12 +22,8 @@ impl Testament {
         vec![Testament::Old, Testament::New].into_iter()
     }
 
-    pub fn _books(&self) -> impl Iterator<Item = &'static str> {
-        book_alias_iter(*self).map(|aliases| (aliases[0]))
-    }
-
-    pub fn books_with_abbrev(&self) -> impl Iterator<Item = (&'static str, &'static str)> {
-        book_alias_iter(*self).map(|aliases| (aliases[0], aliases[1]))
+    pub fn books(&self) -> impl Iterator<Item = &'static str> {
+        book_alias_iter(*self).map(|aliases| aliases[0])
     }
 }
 
@@ -39,11 +36,22 @@ pub fn book(prefix: Option<&str>, alias: Option<&str>) -> Option<&'static str> {
                     .map(|a| (*a, aliases[0]))
                     .collect::<Vec<(&str, &str)>>()
             })
+            .chain(
+                sbl_abbreviation_data()
+                    .iter()
+                    .map(|(book, sbl)| (*sbl, *book))
+            )
             .collect();
     }
 
     match (prefix, alias) {
         (Some(prefix), Some(alias)) => {
+            let prefix = match prefix {
+                "I" => "1",
+                "II" => "2",
+                "III" => "3",
+                _ => prefix,
+            };
             let raw_book = if prefix.is_empty() {
                 alias.to_string()
             } else {
@@ -56,6 +64,22 @@ pub fn book(prefix: Option<&str>, alias: Option<&str>) -> Option<&'static str> {
     }
 }
 
+/// the abbreviation for the canonical book name
+pub fn abbreviation(book: &str) -> Option<&'static str> {
+    lazy_static! {
+        static ref ABBREVIATION_MAP: HashMap<&'static str, &'static str> = all_book_alias_iter()
+            .map(|aliases| (aliases[0], aliases[1]))
+            .collect();
+    }
+
+    ABBREVIATION_MAP.get(book).copied()
+}
+
+/// the SBL Handbook of Style abbreviation for the canonical book name
+pub fn sbl_abbreviation(book: &str) -> Option<&'static str> {
+    sbl_abbreviation_data().get(book).copied()
+}
+
 pub fn is_single_chapter_book(book: &str) -> bool {
     lazy_static! {
         static ref SINGLE_CHAPTER_BOOK_SET: HashSet<&'static str> =
@@ -65,6 +89,11 @@ pub fn is_single_chapter_book(book: &str) -> bool {
     SINGLE_CHAPTER_BOOK_SET.contains(book)
 }
 
+/// all canonical book names, in canonical order
+pub fn all_books() -> impl Iterator<Item = &'static str> {
+    all_book_alias_iter().map(|aliases| aliases[0])
+}
+
 fn all_book_alias_iter() -> impl Iterator<Item = &'static Vec<&'static str>> {
     book_alias_iter(Testament::Old).chain(book_alias_iter(Testament::New))
 }
@@ -167,3 +196,344 @@ fn single_chapter_book_data() -> &'static Vec<&'static str> {
 
     &BOOKS
 }
+
+fn sbl_abbreviation_data() -> &'static HashMap<&'static str, &'static str> {
+    lazy_static! {
+        static ref SBL_ABBREVIATIONS: HashMap<&'static str, &'static str> = HashMap::from([
+            ("Genesis", "Gen"),
+            ("Exodus", "Exod"),
+            ("Leviticus", "Lev"),
+            ("Numbers", "Num"),
+            ("Deuteronomy", "Deut"),
+            ("Joshua", "Josh"),
+            ("Judges", "Judg"),
+            ("Ruth", "Ruth"),
+            ("1 Samuel", "1 Sam"),
+            ("2 Samuel", "2 Sam"),
+            ("1 Kings", "1 Kgs"),
+            ("2 Kings", "2 Kgs"),
+            ("1 Chronicles", "1 Chr"),
+            ("2 Chronicles", "2 Chr"),
+            ("Ezra", "Ezra"),
+            ("Nehemiah", "Neh"),
+            ("Esther", "Esth"),
+            ("Job", "Job"),
+            ("Psalms", "Ps"),
+            ("Proverbs", "Prov"),
+            ("Ecclesiastes", "Eccl"),
+            ("Song of Solomon", "Song"),
+            ("Isaiah", "Isa"),
+            ("Jeremiah", "Jer"),
+            ("Lamentations", "Lam"),
+            ("Ezekiel", "Ezek"),
+            ("Daniel", "Dan"),
+            ("Hosea", "Hos"),
+            ("Joel", "Joel"),
+            ("Amos", "Amos"),
+            ("Obadiah", "Obad"),
+            ("Jonah", "Jonah"),
+            ("Micah", "Mic"),
+            ("Nahum", "Nah"),
+            ("Habakkuk", "Hab"),
+            ("Zephaniah", "Zeph"),
+            ("Haggai", "Hag"),
+            ("Zechariah", "Zech"),
+            ("Malachi", "Mal"),
+            ("Matthew", "Matt"),
+            ("Mark", "Mark"),
+            ("Luke", "Luke"),
+            ("John", "John"),
+            ("Acts", "Acts"),
+            ("Romans", "Rom"),
+            ("1 Corinthians", "1 Cor"),
+            ("2 Corinthians", "2 Cor"),
+            ("Galatians", "Gal"),
+            ("Ephesians", "Eph"),
+            ("Philippians", "Phil"),
+            ("Colossians", "Col"),
+            ("1 Thessalonians", "1 Thess"),
+            ("2 Thessalonians", "2 Thess"),
+            ("1 Timothy", "1 Tim"),
+            ("2 Timothy", "2 Tim"),
+            ("Titus", "Titus"),
+            ("Philemon", "Phlm"),
+            ("Hebrews", "Heb"),
+            ("James", "Jas"),
+            ("1 Peter", "1 Pet"),
+            ("2 Peter", "2 Pet"),
+            ("1 John", "1 John"),
+            ("2 John", "2 John"),
+            ("3 John", "3 John"),
+            ("Jude", "Jude"),
+            ("Revelation", "Rev"),
+        ]);
+    }
+
+    &SBL_ABBREVIATIONS
+}
+
+/// the number of verses in each chapter of the book, in the English versification
+pub fn chapter_verse_counts(book: &str) -> Option<&'static [VInt]> {
+    verse_count_data().get(book).map(|counts| counts.as_slice())
+}
+
+fn verse_count_data() -> &'static HashMap<&'static str, Vec<VInt>> {
+    lazy_static! {
+        static ref VERSE_COUNTS: HashMap<&'static str, Vec<VInt>> = HashMap::from([
+            (
+                "Genesis",
+                vec![
+                    31, 25, 24, 26, 32, 22, 24, 22, 29, 32, 32, 20, 18, 24, 21, 16, 27, 33, 38, 18,
+                    34, 24, 20, 67, 34, 35, 46, 22, 35, 43, 55, 32, 20, 31, 29, 43, 36, 30, 23, 23,
+                    57, 38, 34, 34, 28, 34, 31, 22, 33, 26
+                ]
+            ),
+            (
+                "Exodus",
+                vec![
+                    22, 25, 22, 31, 23, 30, 25, 32, 35, 29, 10, 51, 22, 31, 27, 36, 16, 27, 25, 26,
+                    36, 31, 33, 18, 40, 37, 21, 43, 46, 38, 18, 35, 23, 35, 35, 38, 29, 31, 43, 38
+                ]
+            ),
+            (
+                "Leviticus",
+                vec![
+                    17, 16, 17, 35, 19, 30, 38, 36, 24, 20, 47, 8, 59, 57, 33, 34, 16, 30, 37, 27,
+                    24, 33, 44, 23, 55, 46, 34
+                ]
+            ),
+            (
+                "Numbers",
+                vec![
+                    54, 34, 51, 49, 31, 27, 89, 26, 23, 36, 35, 16, 33, 45, 41, 50, 13, 32, 22, 29,
+                    35, 41, 30, 25, 18, 65, 23, 31, 40, 16, 54, 42, 56, 29, 34, 13
+                ]
+            ),
+            (
+                "Deuteronomy",
+                vec![
+                    46, 37, 29, 49, 33, 25, 26, 20, 29, 22, 32, 32, 18, 29, 23, 22, 20, 22, 21, 20,
+                    23, 30, 25, 22, 19, 19, 26, 68, 29, 20, 30, 52, 29, 12
+                ]
+            ),
+            (
+                "Joshua",
+                vec![
+                    18, 24, 17, 24, 15, 27, 26, 35, 27, 43, 23, 24, 33, 15, 63, 10, 18, 28, 51, 9,
+                    45, 34, 16, 33
+                ]
+            ),
+            (
+                "Judges",
+                vec![
+                    36, 23, 31, 24, 31, 40, 25, 35, 57, 18, 40, 15, 25, 20, 20, 31, 13, 31, 30, 48,
+                    25
+                ]
+            ),
+            ("Ruth", vec![22, 23, 18, 22]),
+            (
+                "1 Samuel",
+                vec![
+                    28, 36, 21, 22, 12, 21, 17, 22, 27, 27, 15, 25, 23, 52, 35, 23, 58, 30, 24, 42,
+                    15, 23, 29, 22, 44, 25, 12, 25, 11, 31, 13
+                ]
+            ),
+            (
+                "2 Samuel",
+                vec![
+                    27, 32, 39, 12, 25, 23, 29, 18, 13, 19, 27, 31, 39, 33, 37, 23, 29, 33, 43, 26,
+                    22, 51, 39, 25
+                ]
+            ),
+            (
+                "1 Kings",
+                vec![
+                    53, 46, 28, 34, 18, 38, 51, 66, 28, 29, 43, 33, 34, 31, 34, 34, 24, 46, 21, 43,
+                    29, 53
+                ]
+            ),
+            (
+                "2 Kings",
+                vec![
+                    18, 25, 27, 44, 27, 33, 20, 29, 37, 36, 21, 21, 25, 29, 38, 20, 41, 37, 37, 21,
+                    26, 20, 37, 20, 30
+                ]
+            ),
+            (
+                "1 Chronicles",
+                vec![
+                    54, 55, 24, 43, 26, 81, 40, 40, 44, 14, 47, 40, 14, 17, 29, 43, 27, 17, 19, 8,
+                    30, 19, 32, 31, 31, 32, 34, 21, 30
+                ]
+            ),
+            (
+                "2 Chronicles",
+                vec![
+                    17, 18, 17, 22, 14, 42, 22, 18, 31, 19, 23, 16, 22, 15, 19, 14, 19, 34, 11, 37,
+                    20, 12, 21, 27, 28, 23, 9, 27, 36, 27, 21, 33, 25, 33, 27, 23
+                ]
+            ),
+            ("Ezra", vec![11, 70, 13, 24, 17, 22, 28, 36, 15, 44]),
+            (
+                "Nehemiah",
+                vec![11, 20, 32, 23, 19, 19, 73, 18, 38, 39, 36, 47, 31]
+            ),
+            ("Esther", vec![22, 23, 15, 17, 14, 14, 10, 17, 32, 3]),
+            (
+                "Job",
+                vec![
+                    22, 13, 26, 21, 27, 30, 21, 22, 35, 22, 20, 25, 28, 22, 35, 22, 16, 21, 29, 29,
+                    34, 30, 17, 25, 6, 14, 23, 28, 25, 31, 40, 22, 33, 37, 16, 33, 24, 41, 30, 24,
+                    34, 17
+                ]
+            ),
+            (
+                "Psalms",
+                vec![
+                    6, 12, 8, 8, 12, 10, 17, 9, 20, 18, 7, 8, 6, 7, 5, 11, 15, 50, 14, 9, 13, 31,
+                    6, 10, 22, 12, 14, 9, 11, 12, 24, 11, 22, 22, 28, 12, 40, 22, 13, 17, 13, 11,
+                    5, 26, 17, 11, 9, 14, 20, 23, 19, 9, 6, 7, 23, 13, 11, 11, 17, 12, 8, 12, 11,
+                    10, 13, 20, 7, 35, 36, 5, 24, 20, 28, 23, 10, 12, 20, 72, 13, 19, 16, 8, 18,
+                    12, 13, 17, 7, 18, 52, 17, 16, 15, 5, 23, 11, 13, 12, 9, 9, 5, 8, 28, 22, 35,
+                    45, 48, 43, 13, 31, 7, 10, 10, 9, 8, 18, 19, 2, 29, 176, 7, 8, 9, 4, 8, 5, 6,
+                    5, 6, 8, 8, 3, 18, 3, 3, 21, 26, 9, 8, 24, 13, 10, 7, 12, 15, 21, 10, 20, 14,
+                    9, 6
+                ]
+            ),
+            (
+                "Proverbs",
+                vec![
+                    33, 22, 35, 27, 23, 35, 27, 36, 18, 32, 31, 28, 25, 35, 33, 33, 28, 24, 29, 30,
+                    31, 29, 35, 34, 28, 28, 27, 28, 27, 33, 31
+                ]
+            ),
+            (
+                "Ecclesiastes",
+                vec![18, 26, 22, 16, 20, 12, 29, 17, 18, 20, 10, 14]
+            ),
+            ("Song of Solomon", vec![17, 17, 11, 16, 16, 13, 13, 14]),
+            (
+                "Isaiah",
+                vec![
+                    31, 22, 26, 6, 30, 13, 25, 22, 21, 34, 16, 6, 22, 32, 9, 14, 14, 7, 25, 6, 17,
+                    25, 18, 23, 12, 21, 13, 29, 24, 33, 9, 20, 24, 17, 10, 22, 38, 22, 8, 31, 29,
+                    25, 28, 28, 25, 13, 15, 22, 26, 11, 23, 15, 12, 17, 13, 12, 21, 14, 21, 22, 11,
+                    12, 19, 12, 25, 24
+                ]
+            ),
+            (
+                "Jeremiah",
+                vec![
+                    19, 37, 25, 31, 31, 30, 34, 22, 26, 25, 23, 17, 27, 22, 21, 21, 27, 23, 15, 18,
+                    14, 30, 40, 10, 38, 24, 22, 17, 32, 24, 40, 44, 26, 22, 19, 32, 21, 28, 18, 16,
+                    18, 22, 13, 30, 5, 28, 7, 47, 39, 46, 64, 34
+                ]
+            ),
+            ("Lamentations", vec![22, 22, 66, 22, 22]),
+            (
+                "Ezekiel",
+                vec![
+                    28, 10, 27, 17, 17, 14, 27, 18, 11, 22, 25, 28, 23, 23, 8, 63, 24, 32, 14, 49,
+                    32, 31, 49, 27, 17, 21, 36, 26, 21, 26, 18, 32, 33, 31, 15, 38, 28, 23, 29, 49,
+                    26, 20, 27, 31, 25, 24, 23, 35
+                ]
+            ),
+            (
+                "Daniel",
+                vec![21, 49, 30, 37, 31, 28, 28, 27, 27, 21, 45, 13]
+            ),
+            (
+                "Hosea",
+                vec![11, 23, 5, 19, 15, 11, 16, 14, 17, 15, 12, 14, 16, 9]
+            ),
+            ("Joel", vec![20, 32, 21]),
+            ("Amos", vec![15, 16, 15, 13, 27, 14, 17, 14, 15]),
+            ("Obadiah", vec![21]),
+            ("Jonah", vec![17, 10, 10, 11]),
+            ("Micah", vec![16, 13, 12, 13, 15, 16, 20]),
+            ("Nahum", vec![15, 13, 19]),
+            ("Habakkuk", vec![17, 20, 19]),
+            ("Zephaniah", vec![18, 15, 20]),
+            ("Haggai", vec![15, 23]),
+            (
+                "Zechariah",
+                vec![21, 13, 10, 14, 11, 15, 14, 23, 17, 12, 17, 14, 9, 21]
+            ),
+            ("Malachi", vec![14, 17, 18, 6]),
+            (
+                "Matthew",
+                vec![
+                    25, 23, 17, 25, 48, 34, 29, 34, 38, 42, 30, 50, 58, 36, 39, 28, 27, 35, 30, 34,
+                    46, 46, 39, 51, 46, 75, 66, 20
+                ]
+            ),
+            (
+                "Mark",
+                vec![45, 28, 35, 41, 43, 56, 37, 38, 50, 52, 33, 44, 37, 72, 47, 20]
+            ),
+            (
+                "Luke",
+                vec![
+                    80, 52, 38, 44, 39, 49, 50, 56, 62, 42, 54, 59, 35, 35, 32, 31, 37, 43, 48, 47,
+                    38, 71, 56, 53
+                ]
+            ),
+            (
+                "John",
+                vec![
+                    51, 25, 36, 54, 47, 71, 53, 59, 41, 42, 57, 50, 38, 31, 27, 33, 26, 40, 42, 31,
+                    25
+                ]
+            ),
+            (
+                "Acts",
+                vec![
+                    26, 47, 26, 37, 42, 15, 60, 40, 43, 48, 30, 25, 52, 28, 41, 40, 34, 28, 41, 38,
+                    40, 30, 35, 27, 27, 32, 44, 31
+                ]
+            ),
+            (
+                "Romans",
+                vec![32, 29, 31, 25, 21, 23, 25, 39, 33, 21, 36, 21, 14, 23, 33, 27]
+            ),
+            (
+                "1 Corinthians",
+                vec![31, 16, 23, 21, 13, 20, 40, 13, 27, 33, 34, 31, 13, 40, 58, 24]
+            ),
+            (
+                "2 Corinthians",
+                vec![24, 17, 18, 18, 21, 18, 16, 24, 15, 18, 33, 21, 14]
+            ),
+            ("Galatians", vec![24, 21, 29, 31, 26, 18]),
+            ("Ephesians", vec![23, 22, 21, 32, 33, 24]),
+            ("Philippians", vec![30, 30, 21, 23]),
+            ("Colossians", vec![29, 23, 25, 18]),
+            ("1 Thessalonians", vec![10, 20, 13, 18, 28]),
+            ("2 Thessalonians", vec![12, 17, 18]),
+            ("1 Timothy", vec![20, 15, 16, 16, 25, 21]),
+            ("2 Timothy", vec![18, 26, 17, 22]),
+            ("Titus", vec![16, 15, 15]),
+            ("Philemon", vec![25]),
+            (
+                "Hebrews",
+                vec![14, 18, 19, 16, 14, 20, 28, 13, 28, 39, 40, 29, 25]
+            ),
+            ("James", vec![27, 26, 18, 17, 20]),
+            ("1 Peter", vec![25, 25, 22, 19, 14]),
+            ("2 Peter", vec![21, 22, 18]),
+            ("1 John", vec![10, 29, 24, 21, 21]),
+            ("2 John", vec![13]),
+            ("3 John", vec![14]),
+            ("Jude", vec![25]),
+            (
+                "Revelation",
+                vec![
+                    20, 29, 22, 11, 14, 17, 17, 13, 21, 11, 19, 17, 18, 20, 8, 21, 18, 24, 21, 15,
+                    27, 21
+                ]
+            ),
+        ]);
+    }
+
+    &VERSE_COUNTS
+}
@@ -0,0 +1,192 @@
+use super::{
+    books::{all_books, chapter_verse_counts},
+    style::Style,
+    AllReferences, CInt, ChapterVerses, VInt,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, Write},
+};
+
+/// how much of a chapter the posts cite
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ChapterCoverage {
+    pub chapter: CInt,
+    /// number of posts citing the chapter
+    pub posts: usize,
+    pub verses_cited: usize,
+    pub verses: usize,
+}
+
+/// how much of a book the posts cite, chapter by chapter
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct BookCoverage {
+    pub book: &'static str,
+    /// number of posts citing the book
+    pub posts: usize,
+    pub verses_cited: usize,
+    pub verses: usize,
+    pub chapters: Vec<ChapterCoverage>,
+}
+
+impl ChapterCoverage {
+    pub fn proportion(&self) -> f64 {
+        proportion(self.verses_cited, self.verses)
+    }
+}
+
+impl BookCoverage {
+    pub fn proportion(&self) -> f64 {
+        proportion(self.verses_cited, self.verses)
+    }
+}
+
+fn proportion(cited: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        cited as f64 / total as f64
+    }
+}
+
+/// the verses of the chapter which are cited, where citing no verses is citing the whole chapter,
+/// and verses before the start or beyond the end of the chapter are ignored
+pub fn cited_verses(cv: &ChapterVerses, verse_count: VInt) -> impl Iterator<Item = VInt> + '_ {
+    let whole_chapter = cv.verses.is_empty().then_some(1..=verse_count);
+    let spans = cv
+        .verses
+        .into_iter()
+        .map(move |vspan| vspan.lower().max(1)..=vspan.upper().min(verse_count));
+
+    whole_chapter.into_iter().chain(spans).flatten()
+}
+
+impl AllReferences {
+    /// coverage of every book in canonical order, counting only passages cited directly
+    pub fn coverage(&self) -> Vec<BookCoverage> {
+        all_books()
+            .filter_map(|book| {
+                chapter_verse_counts(book)
+                    .map(|verse_counts| self.book_coverage(book, verse_counts))
+            })
+            .collect()
+    }
+
+    fn book_coverage(&self, book: &'static str, verse_counts: &[VInt]) -> BookCoverage {
+        let mut posts_by_chapter = HashMap::<CInt, BTreeSet<usize>>::new();
+        let mut verses_by_chapter = HashMap::<CInt, BTreeSet<VInt>>::new();
+
+        for r1 in self
+            .separated_refs_by_book
+            .get(book)
+            .into_iter()
+            .flat_map(|refs1| refs1.iter())
+            .filter(|r1| !r1.via_parallel)
+        {
+            // single chapter books have no chapter in their references
+            let chapter = r1.cv.chapter.map(|chapter| chapter.0).unwrap_or(1);
+            if let Some(verse_count) = (chapter as usize)
+                .checked_sub(1)
+                .and_then(|i| verse_counts.get(i))
+            {
+                posts_by_chapter
+                    .entry(chapter)
+                    .or_default()
+                    .insert(r1.post_index);
+                verses_by_chapter
+                    .entry(chapter)
+                    .or_default()
+                    .extend(cited_verses(&r1.cv, *verse_count));
+            }
+        }
+
+        let chapters = verse_counts
+            .iter()
+            .zip(1..)
+            .map(|(verse_count, chapter)| ChapterCoverage {
+                chapter,
+                posts: posts_by_chapter
+                    .get(&chapter)
+                    .map_or(0, |posts| posts.len()),
+                verses_cited: verses_by_chapter
+                    .get(&chapter)
+                    .map_or(0, |verses| verses.len()),
+                verses: *verse_count as usize,
+            })
+            .collect::<Vec<ChapterCoverage>>();
+
+        BookCoverage {
+            book,
+            posts: posts_by_chapter
+                .values()
+                .flatten()
+                .collect::<BTreeSet<&usize>>()
+                .len(),
+            verses_cited: chapters.iter().map(|c| c.verses_cited).sum(),
+            verses: chapters.iter().map(|c| c.verses).sum(),
+            chapters,
+        }
+    }
+}
+
+const HEATMAP_CELL_STYLE: &str =
+    "display:inline-block; width:0.8em; height:0.8em; margin:1px; border-radius:2px;";
+
+/// the colour for a chapter, from pale grey if not cited, through shades of green as more is cited
+fn heatmap_colour(c: &ChapterCoverage) -> String {
+    if c.posts == 0 {
+        "#eeeeee".to_string()
+    } else {
+        format!("rgba(0, 128, 0, {:.2})", 0.2 + 0.8 * c.proportion())
+    }
+}
+
+fn percentage(proportion: f64) -> String {
+    format!("{:.0}%", proportion * 100.0)
+}
+
+/// write an HTML heatmap of the coverage, a row for each book with a cell for each chapter
+pub fn write_heatmap<'a>(
+    mut f: impl Write,
+    heading: &str,
+    coverage: impl IntoIterator<Item = &'a BookCoverage>,
+    style: &Style,
+) -> io::Result<()> {
+    f.write_all(format!("\n**{}**\n\n", heading).as_bytes())?;
+    f.write_all("<table>\n<tr><th></th><th>Posts</th><th>Verses</th><th></th></tr>\n".as_bytes())?;
+
+    for b in coverage {
+        let cells = b
+            .chapters
+            .iter()
+            .map(|c| {
+                format!(
+                    r#"<span title="{} {}: {} posts, {} of verses" style="{} background-color:{};"></span>"#,
+                    b.book,
+                    c.chapter,
+                    c.posts,
+                    percentage(c.proportion()),
+                    HEATMAP_CELL_STYLE,
+                    heatmap_colour(c)
+                )
+            })
+            .collect::<String>();
+
+        f.write_all(
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                style.book_name(b.book),
+                b.posts,
+                percentage(b.proportion()),
+                cells
+            )
+            .as_bytes(),
+        )?;
+    }
+
+    f.write_all("</table>\n".as_bytes())?;
+
+    Ok(())
+}
+
+mod tests;
@@ -0,0 +1,103 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+use test_case::test_case;
+
+fn book_coverage<'a>(coverage: &'a [BookCoverage], book: &str) -> &'a BookCoverage {
+    coverage.iter().find(|b| b.book == book).unwrap()
+}
+
+#[test]
+fn test_verse_counts_cover_the_whole_bible() {
+    let coverage = AllReferences::new().coverage();
+
+    assert_eq!(coverage.len(), 66);
+    assert_eq!(
+        coverage.iter().map(|b| b.chapters.len()).sum::<usize>(),
+        1189
+    );
+    assert_eq!(coverage.iter().map(|b| b.verses).sum::<usize>(), 31102);
+}
+
+#[test_case(&[(None, "Romans 8:28-30")], 1, 3; "verses")]
+#[test_case(&[(None, "Romans 8:28-30"), (None, "Romans 8:29-31")], 2, 4; "overlapping")]
+#[test_case(&[(None, "Romans 8")], 1, 39; "whole chapter")]
+#[test_case(&[(None, "Romans 8:38-45")], 1, 2; "beyond the end of the chapter")]
+#[test_case(&[(None, "Romans 8:0-39")], 1, 39; "before the start of the chapter")]
+#[test_case(&[(None, "Romans 9:1"), (None, "Romans 8:1 and Romans 9:3")], 1, 1; "other chapters")]
+fn test_chapter_coverage(
+    posts: &[(Option<&str>, &str)],
+    expected_posts: usize,
+    expected_verses_cited: usize,
+) {
+    let coverage = all_references(posts).coverage();
+    let romans_8 = &book_coverage(&coverage, "Romans").chapters[7];
+
+    assert_eq!(romans_8.chapter, 8);
+    assert_eq!(romans_8.posts, expected_posts);
+    assert_eq!(romans_8.verses_cited, expected_verses_cited);
+    assert_eq!(romans_8.verses, 39);
+}
+
+#[test]
+fn test_book_coverage() {
+    let coverage = all_references(&[
+        (None, "Romans 1:1-2 and 8:1"),
+        (None, "Romans 8:1-3"),
+        (None, "Jude v3"),
+    ])
+    .coverage();
+
+    let romans = book_coverage(&coverage, "Romans");
+    assert_eq!(
+        (romans.posts, romans.verses_cited, romans.verses),
+        (2, 5, 433)
+    );
+
+    let jude = book_coverage(&coverage, "Jude");
+    assert_eq!((jude.posts, jude.verses_cited), (1, 1));
+    assert_eq!(jude.chapters[0].posts, 1);
+
+    let genesis = book_coverage(&coverage, "Genesis");
+    assert_eq!((genesis.posts, genesis.verses_cited), (0, 0));
+}
+
+#[test]
+fn test_book_coverage_ignores_chapter_zero() {
+    let coverage = all_references(&[(None, "Romans 0:1 and Romans 8:28")]).coverage();
+
+    let romans = book_coverage(&coverage, "Romans");
+    assert_eq!((romans.posts, romans.verses_cited), (1, 1));
+}
+
+#[test]
+fn test_write_heatmap() {
+    let coverage = all_references(&[(None, "Jude v1-25"), (None, "Philemon 1:1")]).coverage();
+    let mut heatmap = Vec::new();
+    write_heatmap(
+        &mut heatmap,
+        "Coverage",
+        coverage
+            .iter()
+            .filter(|b| ["Philemon", "Jude"].contains(&b.book)),
+        Style::plain(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        String::from_utf8(heatmap).unwrap(),
+        format!(
+            "
+**Coverage**
+
+<table>
+<tr><th></th><th>Posts</th><th>Verses</th><th></th></tr>
+<tr><td>Philemon</td><td>1</td><td>4%</td><td><span title=\"Philemon 1: 1 posts, 4% of verses\" style=\"{} background-color:rgba(0, 128, 0, 0.23);\"></span></td></tr>
+<tr><td>Jude</td><td>1</td><td>100%</td><td><span title=\"Jude 1: 1 posts, 100% of verses\" style=\"{} background-color:rgba(0, 128, 0, 1.00);\"></span></td></tr>
+</table>
+",
+            HEATMAP_CELL_STYLE, HEATMAP_CELL_STYLE
+        )
+    );
+}
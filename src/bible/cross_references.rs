@@ -0,0 +1,139 @@
+use super::*;
+use anyhow::Result;
+use std::{convert::Infallible, io::Write, path::Path};
+
+#[derive(Debug)]
+struct CrossReference {
+    from: ChapterVerses,
+    to_book: &'static str,
+    to: ChapterVerses,
+    votes: i32,
+}
+
+/// A cross-reference dataset, such as the Treasury of Scripture Knowledge
+/// or the OpenBible.info cross references.
+#[derive(Debug)]
+pub struct CrossReferences {
+    by_book: HashMap<&'static str, Vec<CrossReference>>,
+    // lines we couldn't make sense of
+    skipped: usize,
+}
+
+/// a passage related to the one of interest, with its total votes
+#[derive(PartialEq, Eq, Debug)]
+pub struct Related {
+    pub book: &'static str,
+    pub cv: ChapterVerses,
+    pub votes: i32,
+}
+
+impl CrossReferences {
+    /// Load cross-references from a file of tab-separated lines of from and to OSIS references,
+    /// with optional votes, as in the OpenBible.info format, for example `Gen.1.1<TAB>John.1.1-John.1.3<TAB>315`.
+    /// The header line, starting with `From`, is ignored.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        line_file::load(path, |text| {
+            Ok::<_, Infallible>(CrossReferences::from_text(text))
+        })
+    }
+
+    fn from_text(text: &str) -> Self {
+        let mut by_book = HashMap::<&'static str, Vec<CrossReference>>::new();
+        let mut skipped = 0;
+
+        for (_, line) in line_file::entries(text) {
+            if line.starts_with("From") {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let from = fields.next().map(References::from_osis);
+            let to = fields.next().map(References::from_osis);
+            let votes = fields.next().map(|v| v.trim().parse::<i32>()).transpose();
+
+            match (from, to, votes) {
+                (Some(Ok(from)), Some(Ok(to)), Ok(votes)) => {
+                    let votes = votes.unwrap_or(0);
+
+                    for (from_book, from_cvs) in from {
+                        for from_cv in from_cvs {
+                            for (to_book, to_cvs) in to.iter() {
+                                for to_cv in to_cvs {
+                                    by_book.entry(from_book).or_default().push(CrossReference {
+                                        from: from_cv.clone(),
+                                        to_book,
+                                        to: to_cv.clone(),
+                                        votes,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => skipped += 1,
+            }
+        }
+
+        CrossReferences { by_book, skipped }
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// passages cross-referenced from any part of the given passage, most votes first
+    pub fn related(&self, book: &'static str, cv: &ChapterVerses) -> Vec<Related> {
+        let mut related = Vec::<Related>::new();
+
+        for x in self
+            .by_book
+            .get(book)
+            .into_iter()
+            .flatten()
+            .filter(|x| x.from.overlaps(cv))
+        {
+            // the same target may be reached from several verses in the passage
+            match related
+                .iter_mut()
+                .find(|r| r.book == x.to_book && r.cv == x.to)
+            {
+                Some(r) => r.votes += x.votes,
+                None => related.push(Related {
+                    book: x.to_book,
+                    cv: x.to.clone(),
+                    votes: x.votes,
+                }),
+            }
+        }
+
+        related.sort_by_key(|r| std::cmp::Reverse(r.votes));
+        related
+    }
+}
+
+/// write the related passages, each with the posts which already cite them
+pub fn write_related(
+    mut w: impl Write,
+    related: &[Related],
+    posts: &AllReferences,
+) -> std::io::Result<()> {
+    for r in related {
+        writeln!(w, "{} {} ({} votes)", r.book, r.cv, r.votes)?;
+
+        for m in posts.posts_citing(r.book, &r.cv) {
+            writeln!(
+                w,
+                "    {} {}",
+                m.header.title.as_deref().unwrap_or("Unknown"),
+                m.url
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+mod tests;
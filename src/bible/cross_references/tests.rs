@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+const CROSS_REFERENCES: &str = "
+From Verse\tTo Verse\tVotes\t#www.openbible.info CC-BY 2017-01-01
+Gen.1.1\tJohn.1.1-John.1.3\t315
+Gen.1.1\tHeb.11.3\t200
+Gen.1.2\tHeb.11.3\t12
+Gen.1.3\t2Cor.4.6\t40
+Gen.1.1\tXyz.1.1\t5
+Gen.1.1\tPs.33.6
+Gen.3.15\tRom.15.33-Rom.16.20\t8
+Gen.3.24-Gen.4.1\tHeb.11.4\t4
+";
+
+#[test_case("Genesis 1:1", vec!["John 1:1-3 (315)", "Hebrews 11:3 (200)", "Psalms 33:6 (0)"]; "single verse")]
+#[test_case("Genesis 1:1-2", vec!["John 1:1-3 (315)", "Hebrews 11:3 (212)", "Psalms 33:6 (0)"]; "votes aggregated")]
+#[test_case("Genesis 1", vec!["John 1:1-3 (315)", "Hebrews 11:3 (212)", "2 Corinthians 4:6 (40)", "Psalms 33:6 (0)"]; "whole chapter")]
+#[test_case("Genesis 2:1", vec![]; "no overlap")]
+#[test_case("Exodus 1:1", vec![]; "no book")]
+#[test_case("Genesis 3:15", vec!["Romans 15:33 (8)", "Romans 16:1-20 (8)"]; "to across chapters")]
+#[test_case("Genesis 4:1", vec!["Hebrews 11:4 (4)"]; "from across chapters")]
+fn test_related(src: &str, expected: Vec<&str>) {
+    let xrefs = CrossReferences::from_text(CROSS_REFERENCES);
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+    let cv = cvs.into_iter().next().unwrap();
+
+    let result = xrefs
+        .related(book, &cv)
+        .into_iter()
+        .map(|r| format!("{} {} ({})", r.book, r.cv, r.votes))
+        .collect::<Vec<String>>();
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_skipped() {
+    let xrefs = CrossReferences::from_text(CROSS_REFERENCES);
+
+    assert_eq!(xrefs.skipped(), 1);
+}
@@ -0,0 +1,147 @@
+use super::{books::Testament, AllReferences, CInt, ChapterVerses, Cite, Style};
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// a post citing a passage within a chapter, or via a parallel of a passage it cites
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct IndexReference {
+    pub title: String,
+    pub url: String,
+    pub passage: String,
+    pub via_parallel: bool,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct ChapterIndex {
+    pub chapter: CInt,
+    pub references: Vec<IndexReference>,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct BookIndex {
+    pub book: &'static str,
+    /// name of the book in the citation style
+    pub name: String,
+    pub chapters: Vec<ChapterIndex>,
+}
+
+/// The scripture index and references as data for a Hugo theme to render itself.
+/// Posts are keyed by their file path.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ScriptureData {
+    /// books in canonical order, with each chapter cited
+    pub index: Vec<BookIndex>,
+    /// passages cited directly by each post, in canonical order
+    pub posts: BTreeMap<String, Vec<String>>,
+    /// dated posts only
+    pub sequence_numbers: BTreeMap<String, usize>,
+}
+
+impl AllReferences {
+    /// the scripture data, with passages cited in the given style
+    pub fn scripture_data(&mut self, style: &Style) -> ScriptureData {
+        self.calculate_post_sequence_numbers();
+
+        let passage = |book: &'static str, cv: &ChapterVerses| {
+            format!("{} {}", style.book_name(book), cv.styled(style))
+        };
+
+        let mut index = Vec::new();
+        let mut posts = self
+            .metadata
+            .iter()
+            .map(|m| (m.file_path().to_string(), Vec::new()))
+            .collect::<BTreeMap<String, Vec<String>>>();
+
+        for book in Testament::all().flat_map(|testament| testament.books()) {
+            let refs1 = match self.separated_refs_by_book.get(book) {
+                Some(refs1) => refs1,
+                None => continue,
+            };
+
+            let mut chapters = Vec::new();
+            // single chapter books have no chapter in their references
+            for (chapter, group) in &refs1
+                .iter()
+                .group_by(|r1| r1.cv.chapter.map(|chapter| chapter.0).unwrap_or(1))
+            {
+                chapters.push(ChapterIndex {
+                    chapter,
+                    references: group
+                        .map(|r1| {
+                            let m = &self.metadata[r1.post_index];
+                            IndexReference {
+                                title: m.header.title.clone().unwrap_or("Unknown".to_string()),
+                                url: m.url.clone(),
+                                passage: passage(book, &r1.cv),
+                                via_parallel: r1.via_parallel,
+                            }
+                        })
+                        .collect(),
+                });
+            }
+            index.push(BookIndex {
+                book,
+                name: style.book_name(book).to_string(),
+                chapters,
+            });
+
+            for r1 in refs1.iter().filter(|r1| !r1.via_parallel) {
+                if let Some(post_passages) = posts.get_mut(self.metadata[r1.post_index].file_path())
+                {
+                    post_passages.push(passage(book, &r1.cv));
+                }
+            }
+        }
+
+        let sequence_numbers = self
+            .metadata
+            .iter()
+            .zip(self.post_sequence_number_by_index.iter())
+            .filter_map(|(m, sequence_number)| {
+                sequence_number.map(|sequence_number| (m.file_path().to_string(), sequence_number))
+            })
+            .collect();
+
+        ScriptureData {
+            index,
+            posts,
+            sequence_numbers,
+        }
+    }
+}
+
+impl ScriptureData {
+    /// write each part of the data as a JSON file in the directory, creating it if necessary
+    pub fn write<P>(&self, dir: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        fs::create_dir_all(&dir)
+            .context(format!("create_dir_all(\"{}\")", dir.as_ref().display()))?;
+
+        write_json(dir.as_ref().join("index.json"), &self.index)?;
+        write_json(dir.as_ref().join("posts.json"), &self.posts)?;
+        write_json(
+            dir.as_ref().join("sequence_numbers.json"),
+            &self.sequence_numbers,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn write_json<T>(path: impl AsRef<Path>, value: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    let mut json = serde_json::to_string_pretty(value)?;
+    json.push('\n');
+    fs::write(&path, json).context(format!("write(\"{}\")", path.as_ref().display()))?;
+
+    Ok(())
+}
+
+mod tests;
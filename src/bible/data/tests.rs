@@ -0,0 +1,89 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+
+fn scripture_data() -> ScriptureData {
+    all_references(&[
+        (Some("2023-09-30T06:00:00Z"), "Romans 8:28 and Jude v3"),
+        (Some("2023-08-30T06:00:00Z"), "Romans 8:1 and Romans 12:1"),
+        (None, "Genesis 1:1"),
+    ])
+    .scripture_data(Style::plain())
+}
+
+#[test]
+fn test_scripture_data_index() {
+    let index = scripture_data()
+        .index
+        .into_iter()
+        .map(|b| {
+            (
+                b.book,
+                b.chapters
+                    .into_iter()
+                    .map(|c| {
+                        (
+                            c.chapter,
+                            c.references
+                                .into_iter()
+                                .map(|r| (r.title, r.passage))
+                                .collect::<Vec<(String, String)>>(),
+                        )
+                    })
+                    .collect::<Vec<(CInt, Vec<(String, String)>)>>(),
+            )
+        })
+        .collect::<Vec<(&str, Vec<(CInt, Vec<(String, String)>)>)>>();
+
+    let r = |title: &str, passage: &str| (title.to_string(), passage.to_string());
+    assert_eq!(
+        index,
+        vec![
+            ("Genesis", vec![(1, vec![r("Post 2", "Genesis 1:1")])]),
+            (
+                "Romans",
+                vec![
+                    (
+                        8,
+                        vec![r("Post 1", "Romans 8:1"), r("Post 0", "Romans 8:28")]
+                    ),
+                    (12, vec![r("Post 1", "Romans 12:1")])
+                ]
+            ),
+            ("Jude", vec![(1, vec![r("Post 0", "Jude v3")])]),
+        ]
+    );
+}
+
+#[test]
+fn test_scripture_data_posts() {
+    let posts = scripture_data().posts;
+
+    assert_eq!(posts["post/0.md"], vec!["Romans 8:28", "Jude v3"]);
+    assert_eq!(posts["post/1.md"], vec!["Romans 8:1", "Romans 12:1"]);
+    assert_eq!(posts["post/2.md"], vec!["Genesis 1:1"]);
+}
+
+#[test]
+fn test_scripture_data_sequence_numbers() {
+    assert_eq!(
+        scripture_data().sequence_numbers,
+        BTreeMap::from([("post/0.md".to_string(), 2), ("post/1.md".to_string(), 1)])
+    );
+}
+
+#[test]
+fn test_scripture_data_write() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().join("data");
+    scripture_data().write(&dir).unwrap();
+
+    let sequence_numbers = fs::read_to_string(dir.join("sequence_numbers.json")).unwrap();
+    assert_eq!(
+        sequence_numbers,
+        "{\n  \"post/0.md\": 2,\n  \"post/1.md\": 1\n}\n"
+    );
+    assert!(dir.join("index.json").exists());
+    assert!(dir.join("posts.json").exists());
+}
@@ -1,3 +1,5 @@
+use super::books::all_books;
+use super::index_links::quotes;
 use super::*;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -6,7 +8,7 @@ use regex::Regex;
 pub struct ParseError(String);
 
 impl ParseError {
-    fn new<T>(message: T) -> Self
+    pub fn new<T>(message: T) -> Self
     where
         T: Display,
     {
@@ -20,12 +22,14 @@ impl Display for ParseError {
     }
 }
 
+impl std::error::Error for ParseError {}
+
 impl VSpan {
-    fn at(x: VInt) -> Self {
+    pub fn at(x: VInt) -> Self {
         VSpan::Point(x)
     }
 
-    fn between(from: VInt, to: VInt) -> Self {
+    pub fn between(from: VInt, to: VInt) -> Self {
         assert!(from <= to);
 
         VSpan::Line(from, to)
@@ -53,16 +57,16 @@ impl VSpan {
     }
 }
 
-/// trim whitespace and trailing a, b suffix
+/// trim whitespace and trailing a, b suffix, or the start of a following word like "and"
 fn sanitize_verse(s: &str) -> &str {
-    s.trim().trim_end_matches(|c| c == 'a' || c == 'b')
+    s.trim().trim_end_matches(['a', 'b']).trim_end()
 }
 
 impl FromStr for VSpan {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split_once('-') {
+        match s.split_once(['-', '–']) {
             Some((s1, s2)) => match (
                 sanitize_verse(s1).parse::<VInt>(),
                 sanitize_verse(s2).parse::<VInt>(),
@@ -96,7 +100,7 @@ impl VSpans {
         }
     }
 
-    fn insert(&mut self, item: VSpan) {
+    pub fn insert(&mut self, item: VSpan) {
         match self.0.binary_search(&item) {
             Ok(i) => {
                 // repeated insert, ignore
@@ -156,7 +160,7 @@ impl ChaptersVerses {
 }
 
 impl References {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self(HashMap::new())
     }
 
@@ -164,7 +168,11 @@ impl References {
         self.0.get(book)
     }
 
-    fn insert(&mut self, book: &'static str, cv: ChapterVerses) {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn insert(&mut self, book: &'static str, cv: ChapterVerses) {
         match self.0.get_mut(book) {
             Some(entry) => entry.insert(cv),
             None => {
@@ -174,9 +182,14 @@ impl References {
     }
 
     /// non-consuming iterator
-    pub fn _iter(&self) -> std::collections::hash_map::Iter<&'static str, ChaptersVerses> {
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, &'static str, ChaptersVerses> {
         self.0.iter()
     }
+
+    /// non-consuming iterator in canonical book order
+    pub fn iter_canonical(&self) -> impl Iterator<Item = (&'static str, &ChaptersVerses)> {
+        all_books().filter_map(|book| self.0.get(book).map(|cvs| (book, cvs)))
+    }
 }
 
 /// get verses from the text, and return in order
@@ -190,17 +203,19 @@ fn verses(text: &str) -> VSpans {
         .collect::<VSpans>()
 }
 
-pub fn references(_raw_header: &str, text: &str) -> (References, Vec<String>) {
-    lazy_static! {
-        // 1. book chapter, which we use for later context
-        // 2. book chapter:verses, which we extract, and store the context
-        // 3. bare verse, which we extract using the stored context
-        // 4. book verse
-        static ref REFERENCE_RE: Regex =
-            //           (bare verse          )(  prefix     book                  chapter verses)
-            Regex::new(r"(\bv([\dab:,\s-]+))|(([1-3]?)\s*([A-Z][[:alpha:]]+)\s*(\d{1,3}\b)?\s*([:v](\d[abv\d:,\s-]*))?)").unwrap();
-    }
+lazy_static! {
+    // 1. book chapter, which we use for later context
+    // 2. book chapter:verses, which we extract, and store the context
+    // 3. bare verse, which we extract using the stored context
+    // 4. book verse
+    //
+    // Ranges may use either hyphen or en-dash, and the book prefix may be a Roman numeral.
+    pub static ref REFERENCE_RE: Regex =
+        //           (bare verse           )(  prefix                     book                     chapter verses)
+        Regex::new(r"(\bv([\dab:,\s–-]+))|(((?:[1-3]|\bI{1,3}\b)?)\s*([A-Z][[:alpha:]]+)\.?\s*(\d{1,3}\b)?\s*([:v](\d[abv\d:,\s–-]*))?)").unwrap();
+}
 
+pub fn references(_raw_header: &str, text: &str) -> (References, Vec<String>) {
     let mut references = References::new();
     let mut warnings = Vec::new();
 
@@ -255,4 +270,17 @@ pub fn references(_raw_header: &str, text: &str) -> (References, Vec<String>) {
     (references, warnings)
 }
 
+/// references cited as the source of quotes which name their translation
+pub fn quoted_translations(text: &str) -> Vec<(String, References)> {
+    quotes(text)
+        .filter_map(|(_, quote)| match (quote.translation(), quote.source()) {
+            (Some(translation), Some(source)) => {
+                let (refs, _) = references("", source);
+                Some((translation.to_string(), refs))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 mod tests;
@@ -9,6 +9,7 @@ fn test_verses_from_str() {
     assert_eq!(VSpan::from_str("3-5"), Ok(VSpan::between(3, 5)));
     assert_eq!(VSpan::from_str(" 8  "), Ok(VSpan::at(8)));
     assert_eq!(VSpan::from_str(" 13   - 17 "), Ok(VSpan::between(13, 17)));
+    assert_eq!(VSpan::from_str("13–17"), Ok(VSpan::between(13, 17)));
     assert!(VSpan::from_str("abc").is_err());
 }
 
@@ -518,6 +519,9 @@ And Peter remembered the saying of the Lord, how he had said to him, “Before t
 #[test_case("Romans 8:26", "Romans 8:26")]
 #[test_case(" Romans 8:28a", "Romans 8:28")]
 #[test_case("Romans 8:28", "Romans 8:28")]
+#[test_case("Romans 8:28–30", "Romans 8:28-30")]
+#[test_case("Romans 8:28-39 and 12:1", "Romans 8:28-39")]
+#[test_case("Romans 8:28 and", "Romans 8:28")]
 #[test_case("Romans 8:32", "Romans 8:32")]
 #[test_case("Romans 8:33-34a", "Romans 8:33-34")]
 #[test_case("Romans 9:14-15", "Romans 9:14-15")]
@@ -573,3 +577,20 @@ fn test_references(src: &str, expected: &str) {
     let result = format!("{} {}", book, cvs);
     assert_eq!(result, expected, "{}", src);
 }
+
+#[test]
+fn test_quoted_translations() {
+    let text = r#"{{< quote source="Rom 8:38-39" translation="NIV" >}}{{< /quote >}}
+{{< quote source="John 11:35" >}}Jesus wept{{< /quote >}}
+{{< quote source="Ps 23:1" translation="KJV" >}}The LORD is my shepherd{{< /quote >}}"#;
+
+    let result = quoted_translations(text)
+        .into_iter()
+        .map(|(translation, refs)| {
+            let (book, cvs) = refs.into_iter().next().unwrap();
+            format!("{}: {} {}", translation, book, cvs)
+        })
+        .collect::<Vec<String>>();
+
+    assert_eq!(result, vec!["NIV: Romans 8:38-39", "KJV: Psalms 23:1"]);
+}
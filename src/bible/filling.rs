@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+
+use super::extraction::references;
+use super::index_links::{quotes, QUOTE_END_RE};
+use super::text::Texts;
+use anyhow::Result;
+
+/// Return a potentially edited copy of the content with each empty quote filled in with the text
+/// of its source, in canonical book order, along with warnings for quotes which couldn't be filled.  A quote naming its
+/// translation is filled from that translation's store, and any other from the given translation,
+/// which is then recorded in the quote.  Quotes with any text at all are left alone.
+pub fn with_filled_quotes(
+    raw_header: &str,
+    text: &str,
+    texts: &Texts,
+    translation: &str,
+) -> Result<(Option<String>, Vec<String>)> {
+    let mut segments = vec![Cow::Borrowed(raw_header)];
+    let mut warnings = Vec::new();
+    let mut done = 0_usize;
+    let mut updated = false;
+
+    for (span, mut quote) in quotes(text) {
+        let end = match QUOTE_END_RE.find_at(text, span.end) {
+            Some(end) => end,
+            None => continue,
+        };
+        if !text[span.end..end.start()].trim().is_empty() {
+            continue;
+        }
+
+        let source = match quote.source() {
+            Some(source) => source,
+            None => continue,
+        };
+
+        let quote_translation = quote.translation().unwrap_or(translation);
+        let store = match texts.get(Some(quote_translation)) {
+            Some(store) => store,
+            None => {
+                warnings.push(format!(
+                    "no text store for {} for quote from \"{}\"",
+                    quote_translation, source
+                ));
+                continue;
+            }
+        };
+
+        let (refs, _) = references("", source);
+        let mut verses = Vec::new();
+        for (book, cvs) in refs.iter_canonical() {
+            for cv in cvs {
+                verses.push(store.text(book, cv)?);
+            }
+        }
+        let verses = verses.join(" ");
+
+        if verses.trim().is_empty() {
+            warnings.push(format!("no text for quote from \"{}\"", source));
+            continue;
+        }
+
+        if quote.translation().is_none() {
+            quote.set_translation(translation);
+        }
+        segments.push(Cow::Borrowed(&text[done..span.start]));
+        segments.push(Cow::Owned(format!("{}{}", &quote, verses)));
+        done = end.start();
+        updated = true;
+    }
+
+    segments.push(Cow::Borrowed(&text[done..]));
+
+    // only return string if we changed anything
+    if updated {
+        Ok((Some(segments.join("")), warnings))
+    } else {
+        Ok((None, warnings))
+    }
+}
+
+mod tests;
@@ -0,0 +1,60 @@
+#![cfg(test)]
+
+use super::super::text::{temp_store, Texts};
+use super::*;
+use test_case::test_case;
+
+const HEADER: &str = "---\ntitle: \"Conquerors\"\n---\n";
+
+#[test_case(
+    r#"Paul says {{< quote source="Rom 8:38-39" >}}{{< /quote >}} and so"#,
+    Some(r#"Paul says {{< quote source="Rom 8:38-39" translation="WEB" >}}For I am persuaded that neither death, nor life, nor height, nor depth.{{< /quote >}} and so"#),
+    vec![];
+    "empty")]
+#[test_case(
+    "{{< quote source=\"John 11:35\" >}}\n{{< /quote >}}",
+    Some("{{< quote source=\"John 11:35\" translation=\"WEB\" >}}Jesus wept.{{< /quote >}}"),
+    vec![];
+    "whitespace only")]
+#[test_case(
+    r#"{{< quote source="John 11:35" >}}Jesus wept!{{< /quote >}}"#,
+    None,
+    vec![];
+    "not empty")]
+#[test_case(
+    r#"{{< quote source="John 12:1" >}}{{< /quote >}}"#,
+    None,
+    vec!["no text for quote from \"John 12:1\""];
+    "no text")]
+#[test_case(
+    r#"{{< quote source="John 11:35" translation="kjv" >}}{{< /quote >}}"#,
+    Some(r#"{{< quote source="John 11:35" translation="kjv" >}}Jesus wept (KJV).{{< /quote >}}"#),
+    vec![];
+    "own translation")]
+#[test_case(
+    r#"{{< quote source="John 11:35" translation="NIV" >}}{{< /quote >}}"#,
+    None,
+    vec!["no text store for NIV for quote from \"John 11:35\""];
+    "no store for own translation")]
+#[test_case(
+    r#"{{< quote source="Romans 8:39 and John 11:35" >}}{{< /quote >}}"#,
+    Some(r#"{{< quote source="Romans 8:39 and John 11:35" translation="WEB" >}}Jesus wept. nor height, nor depth.{{< /quote >}}"#),
+    vec![];
+    "several books in canonical order")]
+fn test_with_filled_quotes(text: &str, expected: Option<&str>, expected_warnings: Vec<&str>) {
+    let csv = r#"book,chapter,verse,text
+Rom,8,38,"For I am persuaded that neither death, nor life,"
+Rom,8,39,"nor height, nor depth."
+John,11,35,"Jesus wept."
+"#;
+    let (_web_dir, web) = temp_store(csv);
+    let (_kjv_dir, kjv) = temp_store("book,chapter,verse,text\nJohn,11,35,\"Jesus wept (KJV).\"\n");
+    let mut texts = Texts::new(None);
+    texts.insert("WEB", web);
+    texts.insert("KJV", kjv);
+
+    let (result, warnings) = with_filled_quotes(HEADER, text, &texts, "WEB").unwrap();
+
+    assert_eq!(result, expected.map(|text| format!("{}{}", HEADER, text)));
+    assert_eq!(warnings, expected_warnings);
+}
@@ -0,0 +1,224 @@
+use super::{
+    books::{chapter_verse_counts, is_single_chapter_book},
+    style::Style,
+    tabulation::post_date,
+    AllReferences, CInt,
+};
+use crate::hugo::write_table;
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    ops::RangeInclusive,
+};
+use time::{util::days_in_year_month, Date, Month};
+
+/// the chapters of a book which have never been cited, or not for a while
+#[derive(PartialEq, Eq, Debug)]
+pub struct BookGaps {
+    pub book: &'static str,
+    /// proportion of verses cited, in percent
+    pub percent_cited: u8,
+    pub uncited: Vec<RangeInclusive<CInt>>,
+    /// chapters last cited before the cutoff, with the date of their latest citation
+    pub stale: Vec<(CInt, Date)>,
+}
+
+impl BookGaps {
+    pub fn never_cited(&self) -> bool {
+        chapter_verse_counts(self.book)
+            .map(|verse_counts| self.uncited == [1..=verse_counts.len() as CInt])
+            .unwrap_or(false)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.uncited.is_empty() && self.stale.is_empty()
+    }
+}
+
+/// the date the given number of months before, on the same day of the month where possible,
+/// or None if that's out of range
+pub fn months_before(date: Date, months: u32) -> Option<Date> {
+    let month_index =
+        (date.year() * 12 + date.month() as i32 - 1).checked_sub(months.try_into().ok()?)?;
+    let year = month_index.div_euclid(12);
+    let month = Month::try_from(month_index.rem_euclid(12) as u8 + 1).ok()?;
+    let day = date.day().min(days_in_year_month(year, month));
+
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// consecutive chapters as ranges
+fn runs(chapters: impl IntoIterator<Item = CInt>) -> Vec<RangeInclusive<CInt>> {
+    let mut runs: Vec<RangeInclusive<CInt>> = Vec::new();
+    for chapter in chapters {
+        match runs.last_mut() {
+            Some(run) if *run.end() + 1 == chapter => *run = *run.start()..=chapter,
+            _ => runs.push(chapter..=chapter),
+        }
+    }
+
+    runs
+}
+
+fn format_run(run: &RangeInclusive<CInt>) -> String {
+    if run.start() == run.end() {
+        run.start().to_string()
+    } else {
+        format!("{}-{}", run.start(), run.end())
+    }
+}
+
+impl AllReferences {
+    /// the date each chapter was last cited directly, by undated posts not counting
+    fn last_cited(&self) -> HashMap<(&'static str, CInt), Date> {
+        let mut last_cited = HashMap::new();
+
+        for (book, refs1) in self.separated_refs_by_book.iter() {
+            for r1 in refs1.iter().filter(|r1| !r1.via_parallel) {
+                if let Some(date) = post_date(&self.metadata[r1.post_index].header) {
+                    // single chapter books have no chapter in their references
+                    let chapter = r1.cv.chapter.map(|chapter| chapter.0).unwrap_or(1);
+                    let latest = last_cited.entry((*book, chapter)).or_insert(date.date());
+                    *latest = (*latest).max(date.date());
+                }
+            }
+        }
+
+        last_cited
+    }
+
+    /// books with chapters never cited, or last cited before the cutoff date if any, in canonical order
+    pub fn gaps(&self, cutoff: Option<Date>) -> Vec<BookGaps> {
+        let last_cited = self.last_cited();
+
+        self.coverage()
+            .iter()
+            .map(|b| {
+                let uncited = b
+                    .chapters
+                    .iter()
+                    .filter(|c| c.posts == 0)
+                    .map(|c| c.chapter);
+                let stale = cutoff
+                    .map(|cutoff| {
+                        b.chapters
+                            .iter()
+                            .filter_map(|c| {
+                                last_cited
+                                    .get(&(b.book, c.chapter))
+                                    .filter(|date| **date < cutoff)
+                                    .map(|date| (c.chapter, *date))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                BookGaps {
+                    book: b.book,
+                    percent_cited: (b.proportion() * 100.0).round() as u8,
+                    uncited: runs(uncited),
+                    stale,
+                }
+            })
+            .filter(|gaps| !gaps.is_empty())
+            .collect()
+    }
+}
+
+/// write the books never cited, the chapters never cited, and those not cited since the cutoff
+pub fn write_gaps(
+    mut f: impl Write,
+    gaps: &[BookGaps],
+    cutoff: Option<Date>,
+    style: &Style,
+) -> io::Result<()> {
+    let (never_cited, partly_cited): (Vec<&BookGaps>, Vec<&BookGaps>) =
+        gaps.iter().partition(|b| b.never_cited());
+
+    writeln!(f, "\nBooks never cited\n")?;
+    writeln!(
+        f,
+        "{}",
+        never_cited
+            .iter()
+            .map(|b| style.book_name(b.book))
+            .join(", ")
+    )?;
+
+    writeln!(f, "\nChapters never cited\n")?;
+    write_table(
+        &mut f,
+        ["", "Chapters"],
+        partly_cited
+            .iter()
+            .filter(|b| !b.uncited.is_empty())
+            .map(|b| {
+                [
+                    style.book_name(b.book).to_string(),
+                    b.uncited.iter().map(format_run).join(", "),
+                ]
+            }),
+    )?;
+
+    if let Some(cutoff) = cutoff {
+        writeln!(f, "\nChapters last cited before {}\n", cutoff)?;
+        write_table(
+            &mut f,
+            ["", "Last cited"],
+            gaps.iter().flat_map(|b| {
+                b.stale.iter().map(|(chapter, date)| {
+                    [
+                        format!("{} {}", style.book_name(b.book), chapter),
+                        date.to_string(),
+                    ]
+                })
+            }),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write a suggested plan for a series of posts, with the books least cited first.
+/// Each book's passages are the runs of chapters never cited, or not cited for a while.
+pub fn write_plan(mut f: impl Write, gaps: &[BookGaps], style: &Style) -> io::Result<()> {
+    writeln!(f, "\nSuggested series\n")?;
+
+    let by_priority = gaps
+        .iter()
+        .sorted_by_key(|b| b.percent_cited)
+        .collect::<Vec<&BookGaps>>();
+
+    for (i, b) in by_priority.iter().enumerate() {
+        let chapters = b
+            .uncited
+            .iter()
+            .flat_map(|run| run.clone())
+            .chain(b.stale.iter().map(|(chapter, _)| *chapter))
+            .sorted()
+            .collect::<Vec<CInt>>();
+        let book_name = style.book_name(b.book);
+        let passages = if is_single_chapter_book(b.book) {
+            book_name.to_string()
+        } else {
+            runs(chapters)
+                .iter()
+                .map(|run| format!("{} {}", book_name, format_run(run)))
+                .join("; ")
+        };
+
+        writeln!(
+            f,
+            "{}. {} ({}% cited): {}",
+            i + 1,
+            book_name,
+            b.percent_cited,
+            passages
+        )?;
+    }
+
+    Ok(())
+}
+
+mod tests;
@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+use test_case::test_case;
+
+fn date(year: i32, month: u8, day: u8) -> Date {
+    Date::from_calendar_date(year, Month::try_from(month).unwrap(), day).unwrap()
+}
+
+fn book_gaps<'a>(gaps: &'a [BookGaps], book: &str) -> Option<&'a BookGaps> {
+    gaps.iter().find(|b| b.book == book)
+}
+
+#[test_case(date(2024, 5, 15), 3, Some(date(2024, 2, 15)); "same year")]
+#[test_case(date(2024, 2, 10), 3, Some(date(2023, 11, 10)); "previous year")]
+#[test_case(date(2023, 3, 31), 1, Some(date(2023, 2, 28)); "shorter month")]
+#[test_case(date(2024, 3, 31), 1, Some(date(2024, 2, 29)); "leap year")]
+#[test_case(date(2024, 3, 31), 24, Some(date(2022, 3, 31)); "years")]
+#[test_case(date(2024, 3, 31), 200_000, None; "before the earliest date")]
+#[test_case(date(2024, 3, 31), u32::MAX, None; "too many months")]
+fn test_months_before(date: Date, months: u32, expected: Option<Date>) {
+    assert_eq!(months_before(date, months), expected);
+}
+
+#[test]
+fn test_runs() {
+    assert_eq!(runs([1, 2, 3, 5, 7, 8]), vec![1..=3, 5..=5, 7..=8]);
+}
+
+#[test]
+fn test_gaps() {
+    let refs = all_references(&[
+        (Some("2022-03-01T06:00:00Z"), "Romans 1:1 and Romans 3:1"),
+        (Some("2024-03-01T06:00:00Z"), "Romans 3:2 and Romans 16"),
+        (None, "Romans 4:1 and Jude v3"),
+    ]);
+    let gaps = refs.gaps(Some(date(2023, 1, 1)));
+
+    let romans = book_gaps(&gaps, "Romans").unwrap();
+    assert!(!romans.never_cited());
+    assert_eq!(romans.uncited, vec![2..=2, 5..=15]);
+    assert_eq!(romans.stale, vec![(1, date(2022, 3, 1))]);
+
+    assert_eq!(book_gaps(&gaps, "Jude"), None);
+    assert!(book_gaps(&gaps, "Genesis").unwrap().never_cited());
+}
+
+#[test]
+fn test_write_plan() {
+    let refs = all_references(&[
+        (Some("2022-03-01T06:00:00Z"), "Romans 8"),
+        (Some("2024-03-01T06:00:00Z"), "Romans 12"),
+    ]);
+    let gaps = refs
+        .gaps(Some(date(2023, 1, 1)))
+        .into_iter()
+        .filter(|b| ["Romans", "Jude"].contains(&b.book))
+        .collect::<Vec<BookGaps>>();
+    let mut plan = Vec::new();
+    write_plan(&mut plan, &gaps, Style::plain()).unwrap();
+
+    assert_eq!(
+        String::from_utf8(plan).unwrap(),
+        "
+Suggested series
+
+1. Jude (0% cited): Jude
+2. Romans (14% cited): Romans 1-11; Romans 13-16
+"
+    );
+}
@@ -55,7 +55,12 @@ fn book(text: &str) -> Option<&'static str> {
     })
 }
 
-fn quotes(text: &str) -> impl Iterator<Item = (Range<usize>, Quote)> {
+lazy_static! {
+    /// the closing shortcode for a quote
+    pub static ref QUOTE_END_RE: Regex = Regex::new(r"\{\{<\s*/quote\s*>}}").unwrap();
+}
+
+pub fn quotes(text: &str) -> impl Iterator<Item = (Range<usize>, Quote<'_>)> {
     lazy_static! {
         static ref QUOTE_RE: Regex = Regex::new(r"\{\{<\s*quote\s*([^>]*)>}}").unwrap();
         static ref FIELDS_RE: Regex = Regex::new(r#"([a-z]+)="([^"]*)""#).unwrap();
@@ -71,13 +76,17 @@ fn quotes(text: &str) -> impl Iterator<Item = (Range<usize>, Quote)> {
     })
 }
 
-struct Quote<'a>(BTreeMap<&'a str, &'a str>);
+pub struct Quote<'a>(BTreeMap<&'a str, &'a str>);
 
 impl<'a> Quote<'a> {
-    fn source(&self) -> Option<&str> {
+    pub fn source(&self) -> Option<&str> {
         self.0.get("source").copied()
     }
 
+    pub fn translation(&self) -> Option<&str> {
+        self.0.get("translation").copied()
+    }
+
     fn url(&self) -> Option<&str> {
         self.0.get("url").copied()
     }
@@ -85,6 +94,10 @@ impl<'a> Quote<'a> {
     fn set_url(&mut self, value: &'a str) {
         self.0.insert("url", value);
     }
+
+    pub fn set_translation(&mut self, value: &'a str) {
+        self.0.insert("translation", value);
+    }
 }
 
 impl<'a> Display for Quote<'a> {
@@ -0,0 +1,101 @@
+use super::extraction::{references, ParseError};
+use super::*;
+use crate::hugo::{write_table, Create, Header};
+use anyhow::Result;
+use itertools::Itertools;
+use std::{io::Write, path::Path};
+
+/// a lectionary day, such as a Sunday, with its readings
+#[derive(Debug)]
+struct Day {
+    name: String,
+    // each reading as written in the lectionary, with its references
+    readings: Vec<(String, References)>,
+}
+
+/// A lectionary, such as the Revised Common Lectionary, as days with their readings.
+#[derive(Debug)]
+pub struct Lectionary {
+    days: Vec<Day>,
+}
+
+impl Lectionary {
+    const TITLE: &str = "Lectionary";
+    const DESCRIPTION: &str = "Lectionary readings";
+
+    /// Load a lectionary from a file with one day per line, as the date or season
+    /// followed by its readings, all tab-separated, for example
+    /// `Christmas Eve<TAB>Isaiah 9:2-7<TAB>Psalm 96<TAB>Titus 2:11-14<TAB>Luke 2:1-20`.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        line_file::load(path, Lectionary::from_text)
+    }
+
+    fn from_text(text: &str) -> Result<Self, ParseError> {
+        let mut days = Vec::new();
+
+        for (line_number, line) in line_file::entries(text) {
+            let mut fields = line.split('\t').map(|field| field.trim());
+            let name = fields.next().unwrap_or("").to_string();
+
+            let mut readings = Vec::new();
+            for reading in fields.filter(|field| !field.is_empty()) {
+                let (refs, _) = references("", reading);
+                if refs.is_empty() {
+                    return Err(ParseError::new(format!(
+                        "line {}: no reference in '{}'",
+                        line_number, reading
+                    )));
+                }
+                readings.push((reading.to_string(), refs));
+            }
+
+            if readings.is_empty() {
+                return Err(ParseError::new(format!(
+                    "line {}: no readings for {}",
+                    line_number, name
+                )));
+            }
+
+            days.push(Day { name, readings });
+        }
+
+        Ok(Lectionary { days })
+    }
+
+    /// write a page listing each day with its readings and the posts which cite any of them
+    pub fn write(&self, mut c: Box<dyn Create>, posts: &AllReferences) -> Result<()> {
+        let h = Header::new(Self::TITLE, Self::DESCRIPTION);
+        let (mut f, _url) = c.create_leaf(&h)?;
+        f.write_all("\n".as_bytes())?;
+
+        let heading = vec!["Day", "Readings", "Posts"];
+        let body = self
+            .days
+            .iter()
+            .map(|day| {
+                vec![
+                    day.name.clone(),
+                    day.readings
+                        .iter()
+                        .map(|(reading, _)| reading.as_str())
+                        .join(" <br/> "),
+                    posts
+                        .posts_citing_any(day.readings.iter().map(|(_, refs)| refs))
+                        .into_iter()
+                        .map(|m| m.format_href(&None))
+                        .join(" <br/> "),
+                ]
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        write_table(&f, heading, body)?;
+
+        f.flush()?;
+        Ok(())
+    }
+}
+
+mod tests;
@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+use super::*;
+use test_case::test_case;
+
+const LECTIONARY: &str = "
+# Year C
+First Sunday of Advent\tJeremiah 33:14-16\tPsalm 25:1-10\t1 Thessalonians 3:9-13\tLuke 21:25-36
+
+Christmas Eve\tIsaiah 9:2-7\tPsalm 96\tTitus 2:11-14\tLuke 2:1-14, 15-20
+";
+
+#[test]
+fn test_lectionary_from_text() {
+    let lectionary = Lectionary::from_text(LECTIONARY).unwrap();
+    let result = lectionary
+        .days
+        .iter()
+        .map(|day| {
+            (
+                day.name.as_str(),
+                day.readings
+                    .iter()
+                    .flat_map(|(_, refs)| {
+                        refs.iter().map(|(book, cvs)| format!("{} {}", book, cvs))
+                    })
+                    .collect::<Vec<String>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        result,
+        vec![
+            (
+                "First Sunday of Advent",
+                vec![
+                    "Jeremiah 33:14-16".to_string(),
+                    "Psalms 25:1-10".to_string(),
+                    "1 Thessalonians 3:9-13".to_string(),
+                    "Luke 21:25-36".to_string()
+                ]
+            ),
+            (
+                "Christmas Eve",
+                vec![
+                    "Isaiah 9:2-7".to_string(),
+                    "Psalms 96".to_string(),
+                    "Titus 2:11-14".to_string(),
+                    "Luke 2:1-20".to_string()
+                ]
+            ),
+        ]
+    );
+}
+
+#[test_case("Christmas Eve\tIsaiah 9:2-7\tCarols", "parse error: line 1: no reference in 'Carols'"; "not a reference")]
+#[test_case("\nChristmas Eve", "parse error: line 2: no readings for Christmas Eve"; "no readings")]
+fn test_lectionary_from_text_error(src: &str, expected: &str) {
+    assert_eq!(
+        Lectionary::from_text(src).unwrap_err().to_string(),
+        expected
+    );
+}
@@ -0,0 +1,25 @@
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+/// Read a file of one entry per line, as for [`entries`], and parse it, naming the file in any error.
+pub fn load<T, E, P>(path: P, from_text: impl FnOnce(&str) -> Result<T, E>) -> Result<T>
+where
+    P: AsRef<Path>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let text =
+        fs::read_to_string(&path).context(format!("read(\"{}\")", path.as_ref().display()))?;
+
+    from_text(&text).context(format!("parse(\"{}\")", path.as_ref().display()))
+}
+
+/// The trimmed entries of a file of one entry per line, with their line numbers counting from 1.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn entries(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+mod tests;
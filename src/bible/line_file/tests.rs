@@ -0,0 +1,23 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_entries() {
+    let text = "# a comment\nGen.1.1\n\n  \n  Rom.8.28\t\n  # an indented comment\n";
+
+    assert_eq!(
+        entries(text).collect::<Vec<(usize, &str)>>(),
+        vec![(2, "Gen.1.1"), (5, "Rom.8.28")]
+    );
+}
+
+#[test]
+fn test_load_names_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.tsv");
+
+    let e = load(&path, |text| Ok::<_, std::fmt::Error>(text.len())).unwrap_err();
+
+    assert!(format!("{:#}", e).starts_with(&format!("read(\"{}\")", path.display())));
+}
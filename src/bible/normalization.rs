@@ -0,0 +1,91 @@
+use std::{borrow::Cow, ops::Range};
+
+use super::extraction::REFERENCE_RE;
+use super::style::Style;
+use super::*;
+
+/// Return a potentially edited copy of the content with citations rewritten in the given style.
+///
+/// Citations are found by the same parser used to extract references, including those in
+/// quote `source` attributes.  Only the bytes of each citation are touched.
+pub fn normalized(raw_header: &str, text: &str, style: &Style) -> Option<String> {
+    let mut segments = vec![Cow::Borrowed(raw_header)];
+    let mut done = 0_usize;
+    let mut updated = false;
+
+    for (span, citation) in citations(text, style) {
+        if text[span.clone()] != citation {
+            segments.push(Cow::Borrowed(&text[done..span.start]));
+            segments.push(Cow::Owned(citation));
+            done = span.end;
+            updated = true;
+        }
+    }
+
+    segments.push(Cow::Borrowed(&text[done..]));
+
+    // only return string if we changed anything
+    if updated {
+        Some(segments.join(""))
+    } else {
+        None
+    }
+}
+
+/// trim trailing whitespace and punctuation which the parser includes in verses
+fn trim_verses(s: &str) -> &str {
+    s.trim_end_matches(|c: char| c.is_whitespace() || c == ',' || c == '-' || c == '–')
+}
+
+/// the span of each citation in the text, with its rewritten form
+fn citations<'a>(
+    text: &'a str,
+    style: &'a Style,
+) -> impl Iterator<Item = (Range<usize>, String)> + 'a {
+    REFERENCE_RE.captures_iter(text).filter_map(|cap| {
+        if let Some(bare_verses) = cap.get(2) {
+            // bare verse, so everything after the v
+            let verses = trim_verses(bare_verses.as_str());
+            let start = bare_verses.start();
+            return Some((start..start + verses.len(), style.verses(verses)));
+        }
+
+        let prefix = cap.get(4)?;
+        let alias = cap.get(5)?;
+        let book = book(Some(prefix.as_str()), Some(alias.as_str()))?;
+        let chapter = cap.get(6);
+        let verses = cap.get(8);
+
+        // a book on its own is simply a mention, not a citation
+        if chapter.is_none() && verses.is_none() {
+            return None;
+        }
+
+        let start = if prefix.as_str().is_empty() {
+            alias.start()
+        } else {
+            prefix.start()
+        };
+
+        let mut citation = style.book_name(book).to_string();
+        citation.push(' ');
+        let mut end = alias.end();
+
+        if let Some(chapter) = chapter {
+            citation.push_str(chapter.as_str());
+            end = chapter.end();
+        }
+
+        if let Some(verses) = verses {
+            let trimmed = trim_verses(verses.as_str());
+            // the separator, either colon or v
+            citation.push_str(&cap[7][..1]);
+            citation.push_str(&style.verses(trimmed));
+            end = verses.start() + trimmed.len();
+        }
+
+        Some((start..end, citation))
+    })
+}
+
+mod tests;
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::super::style::{BookStyle, Preset};
+use super::*;
+use test_case::test_case;
+
+#[test_case("as in 1 Cor 4:17, we see", "as in 1 Corinthians 4:17, we see")]
+#[test_case("I Cor. 4:17", "1 Corinthians 4:17")]
+#[test_case("Romans 8:28-39 and 12:1 - 2", "Romans 8:28–39 and 12:1 - 2")]
+#[test_case("Ephesians 6:13,15 (also v17-18)", "Ephesians 6:13, 15 (also v17–18)")]
+#[test_case("Psalm 139:1-4, 13-14a.", "Psalms 139:1–4, 13–14a.")]
+#[test_case(
+    r#"{{< quote source="Rom 8:38-39" >}}"#,
+    r#"{{< quote source="Romans 8:38–39" >}}"#
+)]
+fn test_normalized(src: &str, expected: &str) {
+    assert_eq!(
+        normalized("", src, &Style::preset(Preset::Chicago)).as_deref(),
+        Some(expected)
+    );
+}
+
+#[test_case("Romans is a letter"; "book without chapter")]
+#[test_case("Jude v3"; "single chapter book")]
+#[test_case("1 Corinthians 4:17, and Romans 8:28–39"; "already normalized")]
+fn test_normalized_unchanged(src: &str) {
+    assert_eq!(normalized("", src, &Style::preset(Preset::Chicago)), None);
+}
+
+#[test]
+fn test_normalized_abbreviation() {
+    let style = Style {
+        book: BookStyle::Abbreviation,
+        ..Style::preset(Preset::Plain)
+    };
+
+    assert_eq!(
+        normalized("+++\n+++", "1 Corinthians 4:17, 19 – 20", &style).as_deref(),
+        Some("+++\n+++1 Cor 4:17,19-20")
+    );
+}
@@ -0,0 +1,179 @@
+use super::books::{all_books, chapter_verse_counts, sbl_abbreviation};
+use super::extraction::ParseError;
+use super::*;
+use lazy_static::lazy_static;
+
+/// the OSIS book ID, which is the SBL abbreviation without spaces
+pub fn osis_book(book: &'static str) -> String {
+    sbl_abbreviation(book).unwrap_or(book).replace(' ', "")
+}
+
+/// the canonical book name for the OSIS book ID
+pub fn book_from_osis(id: &str) -> Option<&'static str> {
+    lazy_static! {
+        static ref BOOKS_BY_OSIS_ID: HashMap<String, &'static str> =
+            all_books().map(|book| (osis_book(book), book)).collect();
+    }
+
+    BOOKS_BY_OSIS_ID.get(id).copied()
+}
+
+/// OSIS references for the chapter and verses, one for each verse span
+pub fn to_osis(book: &'static str, cv: &ChapterVerses) -> Vec<String> {
+    let id = osis_book(book);
+    // single chapter books have just the one chapter
+    let chapter = cv.chapter.unwrap_or(Chapter(1));
+
+    if cv.verses.is_empty() {
+        vec![format!("{}.{}", id, chapter)]
+    } else {
+        cv.verses
+            .into_iter()
+            .map(|vspan| match vspan {
+                VSpan::Point(v) => format!("{}.{}.{}", id, chapter, v),
+                VSpan::Line(v1, v2) => {
+                    format!("{}.{}.{}-{}.{}.{}", id, chapter, v1, id, chapter, v2)
+                }
+            })
+            .collect()
+    }
+}
+
+/// a single point in an OSIS reference, a book with optional chapter and verse
+pub fn osis_point(s: &str) -> Result<(&'static str, Option<CInt>, Option<VInt>), ParseError> {
+    let mut parts = s.split('.');
+    let id = parts.next().unwrap_or("");
+    let book = book_from_osis(id).ok_or(ParseError::new(format!("unknown OSIS book {}", id)))?;
+    let chapter = parts
+        .next()
+        .map(|c| c.parse::<CInt>())
+        .transpose()
+        .map_err(ParseError::new)?;
+    let verse = parts
+        .next()
+        .map(|v| v.parse::<VInt>())
+        .transpose()
+        .map_err(ParseError::new)?;
+
+    if parts.next().is_some() {
+        Err(ParseError::new(format!("bad OSIS reference {}", s)))
+    } else {
+        Ok((book, chapter, verse))
+    }
+}
+
+fn vspan(v1: VInt, v2: VInt) -> VSpan {
+    if v1 == v2 {
+        VSpan::at(v1)
+    } else {
+        VSpan::between(v1, v2)
+    }
+}
+
+impl References {
+    /// Parse whitespace separated OSIS references, such as `Gen.1.1-Gen.1.3 Rom.8`.
+    ///
+    /// Verse ranges which cross a chapter boundary, such as `Gen.1.31-Gen.2.3`, are split into the rest
+    /// of the first chapter, as far as the English verse count, any whole chapters between, and the start
+    /// of the last chapter.
+    pub fn from_osis(s: &str) -> Result<References, ParseError> {
+        let mut references = References::new();
+
+        for osis_ref in s.split_whitespace() {
+            let (start, end) = match osis_ref.split_once('-') {
+                Some((start, end)) => (osis_point(start)?, osis_point(end)?),
+                None => {
+                    let point = osis_point(osis_ref)?;
+                    (point, point)
+                }
+            };
+
+            let book = start.0;
+            if end.0 != book {
+                return Err(ParseError::new(format!(
+                    "OSIS range across books {}",
+                    osis_ref
+                )));
+            }
+
+            // single chapter books have no chapter in our references
+            let chapter = |c: CInt| {
+                if is_single_chapter_book(book) {
+                    None
+                } else {
+                    Some(Chapter(c))
+                }
+            };
+
+            match (start.1, start.2, end.1, end.2) {
+                (Some(c1), None, Some(c2), None) if c1 <= c2 => {
+                    for c in c1..=c2 {
+                        references.insert(book, ChapterVerses::new(chapter(c), VSpans::new()));
+                    }
+                }
+                (Some(c1), Some(v1), Some(c2), Some(v2)) if c1 == c2 && v1 <= v2 => {
+                    references.insert(
+                        book,
+                        ChapterVerses::new(chapter(c1), VSpans::from_iter([vspan(v1, v2)])),
+                    );
+                }
+                (Some(c1), Some(v1), Some(c2), Some(v2)) if c1 < c2 => {
+                    let end_of_c1 = chapter_verse_counts(book)
+                        .and_then(|counts| counts.get((c1 as usize).checked_sub(1)?))
+                        .filter(|count| **count >= v1)
+                        .ok_or(ParseError::new(format!(
+                            "OSIS range from beyond the end of the chapter {}",
+                            osis_ref
+                        )))?;
+
+                    references.insert(
+                        book,
+                        ChapterVerses::new(chapter(c1), VSpans::from_iter([vspan(v1, *end_of_c1)])),
+                    );
+                    for c in c1 + 1..c2 {
+                        references.insert(book, ChapterVerses::new(chapter(c), VSpans::new()));
+                    }
+                    references.insert(
+                        book,
+                        ChapterVerses::new(chapter(c2), VSpans::from_iter([vspan(1, v2)])),
+                    );
+                }
+                _ => {
+                    return Err(ParseError::new(format!(
+                        "unsupported OSIS reference {}",
+                        osis_ref
+                    )))
+                }
+            }
+        }
+
+        Ok(references)
+    }
+}
+
+impl AllReferences {
+    /// each post's URL with an OSIS reference it cites and the translations it uses for it,
+    /// in canonical book order
+    pub fn osis_index(&self) -> Vec<(&str, String, Vec<&str>)> {
+        all_books()
+            .filter_map(|book| {
+                self.separated_refs_by_book
+                    .get(book)
+                    .map(|refs1| (book, refs1))
+            })
+            .flat_map(|(book, refs1)| {
+                refs1.iter().flat_map(move |r| {
+                    to_osis(book, &r.cv).into_iter().map(|osis_ref| {
+                        (
+                            self.metadata[r.post_index].url.as_str(),
+                            osis_ref,
+                            self.translations(r.post_index, book, [&r.cv]),
+                        )
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+mod tests;
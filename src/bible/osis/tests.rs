@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+use super::super::all_references;
+use super::super::extraction::{quoted_translations, references};
+use super::*;
+use test_case::test_case;
+
+#[test_case("Genesis", "Gen")]
+#[test_case("1 Corinthians", "1Cor")]
+#[test_case("Song of Solomon", "Song")]
+#[test_case("Philemon", "Phlm")]
+fn test_osis_book(book: &'static str, expected: &str) {
+    assert_eq!(osis_book(book), expected);
+    assert_eq!(book_from_osis(expected), Some(book));
+}
+
+#[test_case("Romans 8:28", vec!["Rom.8.28"])]
+#[test_case("Genesis 1:1-3", vec!["Gen.1.1-Gen.1.3"])]
+#[test_case("Ephesians 6:13, 15-17", vec!["Eph.6.13", "Eph.6.15-Eph.6.17"])]
+#[test_case("Psalm 23", vec!["Ps.23"])]
+#[test_case("Jude v3", vec!["Jude.1.3"])]
+fn test_to_osis(src: &str, expected: Vec<&str>) {
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+    let result = cvs
+        .into_iter()
+        .flat_map(|cv| to_osis(book, &cv))
+        .collect::<Vec<String>>();
+
+    assert_eq!(result, expected);
+}
+
+#[test_case("Rom.8.28", "Romans 8:28")]
+#[test_case("Gen.1.1-Gen.1.3", "Genesis 1:1-3")]
+#[test_case("Eph.6.13 Eph.6.15-Eph.6.17", "Ephesians 6:13,15-17")]
+#[test_case("Ps.23-Ps.24", "Psalms 23; 24")]
+#[test_case("Jude.1.3", "Jude v3")]
+#[test_case("Gen.1.31-Gen.2.3", "Genesis 1:31; 2:1-3"; "verses across chapters")]
+#[test_case("Gen.1.30-Gen.3.1", "Genesis 1:30-31; 2; 3:1"; "verses across several chapters")]
+fn test_references_from_osis(src: &str, expected: &str) {
+    let refs = References::from_osis(src).unwrap();
+    let (book, cvs) = refs.into_iter().next().unwrap();
+
+    assert_eq!(format!("{} {}", book, cvs), expected);
+}
+
+#[test_case("Xyz.1.1"; "unknown book")]
+#[test_case("Gen.1.32-Gen.2.3"; "verses across chapters from beyond the end")]
+#[test_case("Gen.51.1-Gen.52.1"; "verses across chapters beyond the book")]
+#[test_case("Gen.50.1-Exod.1.1"; "across books")]
+#[test_case("Gen.1.1.1"; "too many parts")]
+#[test_case("Gen.1.3-Gen.1.1"; "reversed")]
+fn test_references_from_osis_error(src: &str) {
+    assert!(References::from_osis(src).is_err());
+}
+
+#[test]
+fn test_osis_index_translations() {
+    let mut posts = all_references(&[(None, "Romans 8:28-29 and Romans 12:1"), (None, "Jude v3")]);
+    posts.metadata[0].header.translation = Some("ESV".to_string());
+    posts.quoted_translations_by_index[0] = quoted_translations(
+        r#"{{< quote source="Romans 8:29" translation="WEB" >}}{{< /quote >}}"#,
+    );
+
+    assert_eq!(
+        posts.osis_index(),
+        vec![
+            ("/post/0.md", "Rom.8.28-Rom.8.29".to_string(), vec!["WEB"]),
+            ("/post/0.md", "Rom.12.1".to_string(), vec!["ESV"]),
+            ("/post/1.md", "Jude.1.3".to_string(), vec![]),
+        ]
+    );
+}
@@ -0,0 +1,82 @@
+use super::extraction::ParseError;
+use super::*;
+use anyhow::Result;
+use std::path::Path;
+
+/// Groups of parallel passages, such as the same event in the Synoptic Gospels,
+/// or an Old Testament passage and its quotation in the New.
+#[derive(Debug)]
+pub struct Parallels {
+    groups: Vec<Vec<(&'static str, ChapterVerses)>>,
+    // indexes into groups
+    groups_by_book: HashMap<&'static str, Vec<usize>>,
+}
+
+impl Parallels {
+    /// Load parallels from a file with one group per line, as tab-separated OSIS references,
+    /// for example `Mark.4.35-Mark.4.41<TAB>Matt.8.23-Matt.8.27<TAB>Luke.8.22-Luke.8.25`.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        line_file::load(path, Parallels::from_text)
+    }
+
+    fn from_text(text: &str) -> Result<Self, ParseError> {
+        let mut groups = Vec::new();
+        let mut groups_by_book = HashMap::<&'static str, Vec<usize>>::new();
+
+        for (line_number, line) in line_file::entries(text) {
+            let mut group = Vec::new();
+            for field in line.split('\t') {
+                let refs = References::from_osis(field)
+                    .map_err(|e| ParseError::new(format!("line {}: {}", line_number, e)))?;
+
+                for (book, cvs) in refs {
+                    for cv in cvs {
+                        group.push((book, cv));
+                    }
+                }
+            }
+
+            for (book, _) in group.iter() {
+                let group_indices = groups_by_book.entry(book).or_default();
+                if group_indices.last() != Some(&groups.len()) {
+                    group_indices.push(groups.len());
+                }
+            }
+            groups.push(group);
+        }
+
+        Ok(Parallels {
+            groups,
+            groups_by_book,
+        })
+    }
+
+    /// all passages parallel to any which overlap the given passage
+    pub fn parallels_of<'a>(
+        &'a self,
+        book: &'static str,
+        cv: &'a ChapterVerses,
+    ) -> impl Iterator<Item = (&'static str, &'a ChapterVerses)> + 'a {
+        self.groups_by_book
+            .get(book)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.groups[*i])
+            .filter(move |group| {
+                group
+                    .iter()
+                    .any(|(b, parallel_cv)| *b == book && parallel_cv.overlaps(cv))
+            })
+            .flat_map(move |group| {
+                group
+                    .iter()
+                    .filter(move |(b, parallel_cv)| !(*b == book && parallel_cv.overlaps(cv)))
+                    .map(|(b, parallel_cv)| (*b, parallel_cv))
+            })
+    }
+}
+
+mod tests;
@@ -0,0 +1,42 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+const PARALLELS: &str = "
+# calming the storm
+Mark.4.35-Mark.4.41\tMatt.8.23-Matt.8.27\tLuke.8.22-Luke.8.25
+
+Rom.4.3\tGen.15.6
+Gal.3.6\tGen.15.6
+";
+
+#[test_case("Mark 4:38", vec!["Matthew 8:23-27", "Luke 8:22-25"]; "synoptic")]
+#[test_case("Mark 4", vec!["Matthew 8:23-27", "Luke 8:22-25"]; "whole chapter")]
+#[test_case("Mark 4:1-3", vec![]; "no overlap")]
+#[test_case("Genesis 15:6", vec!["Romans 4:3", "Galatians 3:6"]; "several groups")]
+#[test_case("Romans 4:1-5", vec!["Genesis 15:6"]; "quotation")]
+fn test_parallels_of(src: &str, expected: Vec<&str>) {
+    let parallels = Parallels::from_text(PARALLELS).unwrap();
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+    let cv = cvs.into_iter().next().unwrap();
+
+    let result = parallels
+        .parallels_of(book, &cv)
+        .map(|(b, cv)| format!("{} {}", b, cv))
+        .collect::<Vec<String>>();
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_parallels_from_text_error() {
+    assert_eq!(
+        Parallels::from_text("Rom.4.3\tGen.15.6\nRom.4.3\tXyz.1.1")
+            .unwrap_err()
+            .to_string(),
+        "parse error: line 2: parse error: unknown OSIS book Xyz"
+    );
+}
@@ -0,0 +1,103 @@
+use super::extraction::ParseError;
+use super::osis::osis_point;
+use super::*;
+use anyhow::Result;
+use itertools::Itertools;
+use std::path::Path;
+
+/// a chapter and verse, where the chapter of a single chapter book is 1
+type Point = (CInt, VInt);
+
+/// a section heading and the verses it covers
+#[derive(Debug)]
+struct Pericope {
+    start: Point,
+    end: Point,
+    title: String,
+}
+
+impl Pericope {
+    fn overlaps(&self, cv: &ChapterVerses) -> bool {
+        let chapter = cv.chapter.unwrap_or(Chapter(1)).0;
+
+        if cv.verses.is_empty() {
+            self.start.0 <= chapter && chapter <= self.end.0
+        } else {
+            cv.verses.into_iter().any(|vspan| {
+                self.start <= (chapter, vspan.upper()) && (chapter, vspan.lower()) <= self.end
+            })
+        }
+    }
+}
+
+/// Section headings, also known as pericope titles, for annotating references.
+#[derive(Default, Debug)]
+pub struct Pericopes {
+    // in order of start within each book
+    by_book: HashMap<&'static str, Vec<Pericope>>,
+}
+
+impl Pericopes {
+    /// Load section headings from a file with one heading per line, as the tab-separated
+    /// OSIS start and end verses and title, for example `Rom.8.31<TAB>Rom.8.39<TAB>More than conquerors`.
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        line_file::load(path, Pericopes::from_text)
+    }
+
+    fn from_text(text: &str) -> Result<Self, ParseError> {
+        let mut by_book = HashMap::<&'static str, Vec<Pericope>>::new();
+
+        for (line_number, line) in line_file::entries(text) {
+            let error = |e: String| ParseError::new(format!("line {}: {}", line_number, e));
+
+            let fields = line
+                .split('\t')
+                .map(|field| field.trim())
+                .collect::<Vec<_>>();
+            let (start, end, title) = match fields[..] {
+                [start, end, title] if !title.is_empty() => (start, end, title),
+                _ => return Err(error("expected start, end and title".to_string())),
+            };
+
+            let point = |s: &str| match osis_point(s).map_err(|e| error(e.to_string()))? {
+                (book, Some(chapter), Some(verse)) => Ok((book, (chapter, verse))),
+                _ => Err(error(format!("expected OSIS verse, found {}", s))),
+            };
+
+            let (start_book, start) = point(start)?;
+            let (end_book, end) = point(end)?;
+
+            if start_book != end_book || start > end {
+                return Err(error(format!("bad pericope {} {}", fields[0], fields[1])));
+            }
+
+            let pericope = Pericope {
+                start,
+                end,
+                title: title.to_string(),
+            };
+            let pericopes = by_book.entry(start_book).or_default();
+            let i = pericopes.partition_point(|p| p.start <= pericope.start);
+            pericopes.insert(i, pericope);
+        }
+
+        Ok(Pericopes { by_book })
+    }
+
+    /// titles of the sections in which the references fall, in order and without repeats
+    pub fn titles(&self, book: &str, cvs: &ChaptersVerses) -> Vec<&str> {
+        self.by_book
+            .get(book)
+            .into_iter()
+            .flatten()
+            .filter(|p| cvs.into_iter().any(|cv| p.overlaps(cv)))
+            .map(|p| p.title.as_str())
+            .unique()
+            .collect()
+    }
+}
+
+mod tests;
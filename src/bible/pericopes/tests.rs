@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+const PERICOPES: &str = "
+# Romans
+Rom.8.31\tRom.8.39\tMore than conquerors
+Rom.8.18\tRom.8.30\tFuture glory
+Rom.9.1\tRom.9.5\tPaul's anguish over Israel
+
+Gen.1.1\tGen.2.3\tThe beginning
+Jude.1.1\tJude.1.2\tGreeting
+Mark.1.1\tMark.1.8\tJohn the Baptist
+Mark.1.9\tMark.1.11\tJohn the Baptist
+";
+
+#[test_case("Romans 8:28-39", vec!["Future glory", "More than conquerors"]; "two sections")]
+#[test_case("Romans 8:35", vec!["More than conquerors"]; "within section")]
+#[test_case("Romans 8", vec!["Future glory", "More than conquerors"]; "whole chapter")]
+#[test_case("Romans 8:1-4", vec![]; "no section")]
+#[test_case("Romans 8:38 and Romans 9:3", vec!["More than conquerors", "Paul's anguish over Israel"]; "several chapters")]
+#[test_case("Genesis 2:1", vec!["The beginning"]; "across chapters")]
+#[test_case("Jude v2", vec!["Greeting"]; "single chapter book")]
+#[test_case("Mark 1:4-10", vec!["John the Baptist"]; "repeated title")]
+fn test_titles(src: &str, expected: Vec<&str>) {
+    let pericopes = Pericopes::from_text(PERICOPES).unwrap();
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+
+    assert_eq!(pericopes.titles(book, &cvs), expected);
+}
+
+#[test_case("Rom.8.31\tRom.8.39", "parse error: line 1: expected start, end and title"; "missing title")]
+#[test_case("Rom.8.31\tRom.8\tMore", "parse error: line 1: expected OSIS verse, found Rom.8"; "not a verse")]
+#[test_case("Rom.8.39\tRom.8.31\tMore", "parse error: line 1: bad pericope Rom.8.39 Rom.8.31"; "reversed")]
+#[test_case("Rom.8.31\tXyz.8.39\tMore", "parse error: line 1: parse error: unknown OSIS book Xyz"; "unknown book")]
+fn test_pericopes_from_text_error(src: &str, expected: &str) {
+    assert_eq!(Pericopes::from_text(src).unwrap_err().to_string(), expected);
+}
@@ -0,0 +1,111 @@
+use super::{books::chapter_verse_counts, coverage::cited_verses, AllReferences, CInt, VInt};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
+};
+
+/// score for each book both posts cite
+const BOOK_WEIGHT: u32 = 1;
+/// score for each chapter both posts cite
+const CHAPTER_WEIGHT: u32 = 2;
+/// score for each verse both posts cite, so posts expounding the same passage rank highest
+const VERSE_WEIGHT: u32 = 4;
+
+/// a post related to another by the passages they both cite
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct RelatedPost {
+    pub title: String,
+    /// content path, as for relref
+    pub url: String,
+    pub score: u32,
+}
+
+impl AllReferences {
+    /// Posts related to each post by the passages they both cite directly, highest score first,
+    /// at most limit for each post.
+    /// Posts are keyed by their file path.
+    pub fn related_posts(&self, limit: usize) -> BTreeMap<String, Vec<RelatedPost>> {
+        let mut posts_by_book = HashMap::<&'static str, BTreeSet<usize>>::new();
+        let mut posts_by_chapter = HashMap::<(&'static str, CInt), BTreeSet<usize>>::new();
+        let mut posts_by_verse = HashMap::<(&'static str, CInt, VInt), BTreeSet<usize>>::new();
+
+        for (book, refs1) in self.separated_refs_by_book.iter() {
+            let verse_counts = chapter_verse_counts(book).unwrap_or_default();
+
+            for r1 in refs1.iter().filter(|r1| !r1.via_parallel) {
+                // single chapter books have no chapter in their references
+                let chapter = r1.cv.chapter.map(|chapter| chapter.0).unwrap_or(1);
+
+                posts_by_book.entry(book).or_default().insert(r1.post_index);
+                posts_by_chapter
+                    .entry((book, chapter))
+                    .or_default()
+                    .insert(r1.post_index);
+                if let Some(verse_count) = (chapter as usize)
+                    .checked_sub(1)
+                    .and_then(|i| verse_counts.get(i))
+                {
+                    for verse in cited_verses(&r1.cv, *verse_count) {
+                        posts_by_verse
+                            .entry((book, chapter, verse))
+                            .or_default()
+                            .insert(r1.post_index);
+                    }
+                }
+            }
+        }
+
+        let mut scores = HashMap::<(usize, usize), u32>::new();
+        for (posts, weight) in posts_by_book
+            .values()
+            .map(|posts| (posts, BOOK_WEIGHT))
+            .chain(
+                posts_by_chapter
+                    .values()
+                    .map(|posts| (posts, CHAPTER_WEIGHT)),
+            )
+            .chain(posts_by_verse.values().map(|posts| (posts, VERSE_WEIGHT)))
+        {
+            for post_a in posts.iter() {
+                for post_b in posts.iter().filter(|post_b| *post_b != post_a) {
+                    *scores.entry((*post_a, *post_b)).or_default() += weight;
+                }
+            }
+        }
+
+        let mut related_by_post = BTreeMap::<usize, Vec<(usize, u32)>>::new();
+        for ((post_a, post_b), score) in scores {
+            related_by_post
+                .entry(post_a)
+                .or_default()
+                .push((post_b, score));
+        }
+
+        related_by_post
+            .into_iter()
+            .map(|(post_index, mut related)| {
+                related.sort_by_key(|(other, score)| (Reverse(*score), *other));
+                related.truncate(limit);
+
+                (
+                    self.metadata[post_index].file_path().to_string(),
+                    related
+                        .into_iter()
+                        .map(|(other, score)| RelatedPost {
+                            title: self.metadata[other]
+                                .header
+                                .title
+                                .clone()
+                                .unwrap_or("Unknown".to_string()),
+                            url: self.metadata[other].url.clone(),
+                            score,
+                        })
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+mod tests;
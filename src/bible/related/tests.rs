@@ -0,0 +1,55 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+use test_case::test_case;
+
+fn related(related_posts: &BTreeMap<String, Vec<RelatedPost>>, key: &str) -> Vec<(String, u32)> {
+    related_posts
+        .get(key)
+        .into_iter()
+        .flatten()
+        .map(|r| (r.title.clone(), r.score))
+        .collect()
+}
+
+#[test]
+fn test_related_posts() {
+    let related_posts = all_references(&[
+        (None, "Romans 8:28-30"),
+        (None, "Romans 8:28"),
+        (None, "Romans 12:1"),
+        (None, "Genesis 1:1"),
+    ])
+    .related_posts(1);
+
+    assert_eq!(
+        related(&related_posts, "post/0.md"),
+        vec![("Post 1".to_string(), 7)]
+    );
+    assert_eq!(
+        related(&related_posts, "post/2.md"),
+        vec![("Post 0".to_string(), 1)]
+    );
+    assert_eq!(related_posts["post/0.md"][0].url, "/post/1.md");
+    assert!(!related_posts.contains_key("post/3.md"));
+}
+
+#[test_case("Romans 8:28-30", "Romans 8:29-31", 1 + 2 + 2 * 4; "overlapping verses")]
+#[test_case("Romans 8", "Romans 8:28-30", 1 + 2 + 3 * 4; "whole chapter")]
+#[test_case("Romans 8:1", "Romans 8:2", 1 + 2; "same chapter")]
+#[test_case("Romans 1:1", "Romans 8:1", 1; "same book")]
+#[test_case("Jude v3", "Jude v3-4", 1 + 2 + 4; "single chapter book")]
+#[test_case("Romans 0:1", "Romans 0:1", 1 + 2; "chapter zero")]
+fn test_related_posts_score(text_a: &str, text_b: &str, expected: u32) {
+    let related_posts = all_references(&[(None, text_a), (None, text_b)]).related_posts(5);
+
+    assert_eq!(
+        related(&related_posts, "post/0.md"),
+        vec![("Post 1".to_string(), expected)]
+    );
+    assert_eq!(
+        related(&related_posts, "post/1.md"),
+        vec![("Post 0".to_string(), expected)]
+    );
+}
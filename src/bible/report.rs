@@ -0,0 +1,181 @@
+use super::{books::Testament, AllReferences, Style};
+use crate::hugo::write_table;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+    io::{self, Write},
+};
+
+/// a post whose direct citations of a book are laid out in more than one row
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct SplitPost {
+    pub book: String,
+    pub title: String,
+    pub url: String,
+    pub rows: usize,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct PostRows {
+    pub title: String,
+    pub url: String,
+    /// rows in the index, counting those via a parallel
+    pub rows: usize,
+    pub books: usize,
+}
+
+/// a book where some posts' citations couldn't be merged into a single row,
+/// because another post cites a chapter in between
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct InterruptedBook {
+    pub book: String,
+    pub rows: usize,
+    /// posts laid out in more than one row
+    pub posts: usize,
+    /// rows beyond one for each of those posts
+    pub extra_rows: usize,
+}
+
+/// Diagnostics of how the scripture index lays out its rows.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Report {
+    /// in canonical order of book, most rows first within a book
+    pub split_posts: Vec<SplitPost>,
+    /// most rows first
+    pub most_rows: Vec<PostRows>,
+    /// most extra rows first, keeping canonical order among equals
+    pub interrupted_books: Vec<InterruptedBook>,
+}
+
+impl AllReferences {
+    /// report on the layout of the coalesced references, with at most limit of the posts with most rows
+    pub fn report(&self, style: &Style, limit: usize) -> Report {
+        let title = |post_index: usize| {
+            self.metadata[post_index]
+                .header
+                .title
+                .clone()
+                .unwrap_or("Unknown".to_string())
+        };
+
+        let mut split_posts = Vec::new();
+        let mut interrupted_books = Vec::new();
+        let mut rows_by_post = vec![0; self.metadata.len()];
+        let mut books_by_post = vec![0; self.metadata.len()];
+
+        for book in Testament::all().flat_map(|testament| testament.books()) {
+            let refs = match self.refs_by_book.get(book) {
+                Some(refs) => refs,
+                None => continue,
+            };
+
+            let mut posts = HashSet::new();
+            let mut direct_rows_by_post = BTreeMap::<usize, usize>::new();
+            for r in refs.iter() {
+                rows_by_post[r.post_index] += 1;
+                if posts.insert(r.post_index) {
+                    books_by_post[r.post_index] += 1;
+                }
+                if !r.via_parallel {
+                    *direct_rows_by_post.entry(r.post_index).or_default() += 1;
+                }
+            }
+
+            let mut book_split_posts = direct_rows_by_post
+                .into_iter()
+                .filter(|(_, rows)| *rows > 1)
+                .map(|(post_index, rows)| SplitPost {
+                    book: style.book_name(book).to_string(),
+                    title: title(post_index),
+                    url: self.metadata[post_index].url.clone(),
+                    rows,
+                })
+                .collect::<Vec<SplitPost>>();
+            book_split_posts.sort_by_key(|p| Reverse(p.rows));
+
+            if !book_split_posts.is_empty() {
+                interrupted_books.push(InterruptedBook {
+                    book: style.book_name(book).to_string(),
+                    rows: refs.iter().count(),
+                    posts: book_split_posts.len(),
+                    extra_rows: book_split_posts.iter().map(|p| p.rows - 1).sum(),
+                });
+            }
+            split_posts.extend(book_split_posts);
+        }
+        interrupted_books.sort_by_key(|b| Reverse(b.extra_rows));
+
+        let mut most_rows = rows_by_post
+            .iter()
+            .zip(books_by_post.iter())
+            .enumerate()
+            .filter(|(_, (rows, _))| **rows > 0)
+            .map(|(post_index, (rows, books))| PostRows {
+                title: title(post_index),
+                url: self.metadata[post_index].url.clone(),
+                rows: *rows,
+                books: *books,
+            })
+            .collect::<Vec<PostRows>>();
+        most_rows.sort_by_key(|p| Reverse(p.rows));
+        most_rows.truncate(limit);
+
+        Report {
+            split_posts,
+            most_rows,
+            interrupted_books,
+        }
+    }
+}
+
+impl Report {
+    /// write the report as a series of tables
+    pub fn write_tables(&self, mut f: impl Write) -> io::Result<()> {
+        writeln!(f, "Posts split across rows\n")?;
+        write_table(
+            &mut f,
+            ["", "", "", "Rows"],
+            self.split_posts.iter().map(|p| {
+                [
+                    p.book.clone(),
+                    p.title.clone(),
+                    p.url.clone(),
+                    p.rows.to_string(),
+                ]
+            }),
+        )?;
+
+        writeln!(f, "\nPosts with most rows\n")?;
+        write_table(
+            &mut f,
+            ["", "", "Rows", "Books"],
+            self.most_rows.iter().map(|p| {
+                [
+                    p.title.clone(),
+                    p.url.clone(),
+                    p.rows.to_string(),
+                    p.books.to_string(),
+                ]
+            }),
+        )?;
+
+        writeln!(f, "\nBooks with rows which couldn't be merged\n")?;
+        write_table(
+            &mut f,
+            ["", "Rows", "Posts split", "Extra rows"],
+            self.interrupted_books.iter().map(|b| {
+                [
+                    b.book.clone(),
+                    b.rows.to_string(),
+                    b.posts.to_string(),
+                    b.extra_rows.to_string(),
+                ]
+            }),
+        )?;
+
+        Ok(())
+    }
+}
+
+mod tests;
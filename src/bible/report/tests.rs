@@ -0,0 +1,66 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+
+fn report() -> Report {
+    let mut all = all_references(&[
+        (
+            None,
+            "Romans 1:1 and Romans 3:1 and Romans 5:1 and Genesis 1:1",
+        ),
+        (None, "Romans 2:1 and Romans 4:1"),
+        (None, "Romans 8:28 and Romans 9:1"),
+        (None, "no references here"),
+    ]);
+    all.coalesce();
+
+    all.report(Style::plain(), 2)
+}
+
+#[test]
+fn test_report_split_posts() {
+    assert_eq!(
+        report()
+            .split_posts
+            .iter()
+            .map(|p| (p.book.as_str(), p.title.as_str(), p.rows))
+            .collect::<Vec<(&str, &str, usize)>>(),
+        vec![("Romans", "Post 0", 3), ("Romans", "Post 1", 2)]
+    );
+}
+
+#[test]
+fn test_report_most_rows() {
+    assert_eq!(
+        report()
+            .most_rows
+            .iter()
+            .map(|p| (p.title.as_str(), p.rows, p.books))
+            .collect::<Vec<(&str, usize, usize)>>(),
+        vec![("Post 0", 4, 2), ("Post 1", 2, 1)]
+    );
+}
+
+#[test]
+fn test_report_interrupted_books() {
+    assert_eq!(
+        report().interrupted_books,
+        vec![InterruptedBook {
+            book: "Romans".to_string(),
+            rows: 6,
+            posts: 2,
+            extra_rows: 3,
+        }]
+    );
+}
+
+#[test]
+fn test_report_nothing_split() {
+    let mut all = all_references(&[(None, "Romans 1:1 and Romans 2:1"), (None, "Romans 8:28")]);
+    all.coalesce();
+    let report = all.report(Style::plain(), 10);
+
+    assert!(report.split_posts.is_empty());
+    assert!(report.interrupted_books.is_empty());
+}
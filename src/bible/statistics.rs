@@ -0,0 +1,270 @@
+use super::{
+    books::Testament, tabulation::post_date, AllReferences, Chapter, ChapterVerses, Cite, Style,
+    VInt, VSpan, VSpans,
+};
+use crate::hugo::write_table;
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    io::{self, Write},
+};
+
+/// a passage and the number of posts which cite it
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Cited {
+    pub passage: String,
+    pub posts: usize,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct TestamentCitations {
+    pub testament: &'static str,
+    pub posts: usize,
+    pub citations: usize,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct PostCitations {
+    pub title: String,
+    pub url: String,
+    pub citations: usize,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct YearCitations {
+    pub year: i32,
+    pub posts: usize,
+    pub citations: usize,
+}
+
+/// Citation statistics, where a citation is a post citing a chapter, or verses within it.
+/// Only passages cited directly are counted, not those via a parallel.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Statistics {
+    pub posts: usize,
+    pub citations: usize,
+    pub books: Vec<Cited>,
+    pub chapters: Vec<Cited>,
+    pub verses: Vec<Cited>,
+    pub testaments: Vec<TestamentCitations>,
+    /// every post, most citations first
+    pub citations_per_post: Vec<PostCitations>,
+    /// dated posts only
+    pub years: Vec<YearCitations>,
+    /// passages cited by more than one post, counting posts which cite any overlapping passage
+    pub discussed: Vec<Cited>,
+}
+
+/// the most cited first, keeping canonical order among equals, and at most limit of them
+fn most_cited(mut cited: Vec<Cited>, limit: usize) -> Vec<Cited> {
+    cited.sort_by_key(|c| Reverse(c.posts));
+    cited.truncate(limit);
+    cited
+}
+
+impl AllReferences {
+    /// statistics of citations, with at most limit of each of the most cited books, chapters, verses
+    /// and passages
+    pub fn statistics(&self, style: &Style, limit: usize) -> Statistics {
+        let passage = |book: &'static str, cv: &ChapterVerses| {
+            format!("{} {}", style.book_name(book), cv.styled(style))
+        };
+
+        let mut books = Vec::new();
+        let mut chapters = Vec::new();
+        let mut verses = Vec::new();
+        let mut discussed = Vec::new();
+        let mut citations_by_post = vec![0; self.metadata.len()];
+        let mut posts_by_testament = BTreeMap::<&'static str, HashSet<usize>>::new();
+        let mut citations_by_testament = BTreeMap::<&'static str, usize>::new();
+
+        for testament in Testament::all() {
+            for book in testament.books() {
+                let mut book_posts = BTreeSet::new();
+                let mut chapter_posts = BTreeMap::<Option<Chapter>, BTreeSet<usize>>::new();
+                let mut verse_posts = BTreeMap::<(Option<Chapter>, VInt), BTreeSet<usize>>::new();
+                let mut passages = BTreeSet::<&ChapterVerses>::new();
+
+                for r1 in self
+                    .separated_refs_by_book
+                    .get(book)
+                    .into_iter()
+                    .flat_map(|refs1| refs1.iter())
+                    .filter(|r1| !r1.via_parallel)
+                {
+                    citations_by_post[r1.post_index] += 1;
+                    *citations_by_testament.entry(testament.name()).or_default() += 1;
+                    posts_by_testament
+                        .entry(testament.name())
+                        .or_default()
+                        .insert(r1.post_index);
+
+                    book_posts.insert(r1.post_index);
+                    chapter_posts
+                        .entry(r1.cv.chapter)
+                        .or_default()
+                        .insert(r1.post_index);
+                    for vspan in &r1.cv.verses {
+                        for verse in vspan.lower()..=vspan.upper() {
+                            verse_posts
+                                .entry((r1.cv.chapter, verse))
+                                .or_default()
+                                .insert(r1.post_index);
+                        }
+                    }
+                    passages.insert(&r1.cv);
+                }
+
+                if !book_posts.is_empty() {
+                    books.push(Cited {
+                        passage: style.book_name(book).to_string(),
+                        posts: book_posts.len(),
+                    });
+                }
+                chapters.extend(
+                    chapter_posts
+                        .into_iter()
+                        // single chapter books are cited without a chapter
+                        .filter_map(|(chapter, posts)| chapter.map(|chapter| (chapter, posts)))
+                        .map(|(chapter, posts)| Cited {
+                            passage: passage(
+                                book,
+                                &ChapterVerses::new(Some(chapter), VSpans::new()),
+                            ),
+                            posts: posts.len(),
+                        }),
+                );
+                verses.extend(verse_posts.into_iter().map(|((chapter, verse), posts)| {
+                    let cv = ChapterVerses::new(chapter, VSpans::from_iter([VSpan::at(verse)]));
+                    Cited {
+                        passage: passage(book, &cv),
+                        posts: posts.len(),
+                    }
+                }));
+                discussed.extend(passages.into_iter().filter_map(|cv| {
+                    let posts = self
+                        .post_indices_citing(book, cv)
+                        .collect::<HashSet<usize>>()
+                        .len();
+                    (posts > 1).then(|| Cited {
+                        passage: passage(book, cv),
+                        posts,
+                    })
+                }));
+            }
+        }
+
+        let testaments = Testament::all()
+            .map(|testament| TestamentCitations {
+                testament: testament.name(),
+                posts: posts_by_testament
+                    .get(testament.name())
+                    .map_or(0, |posts| posts.len()),
+                citations: citations_by_testament
+                    .get(testament.name())
+                    .copied()
+                    .unwrap_or(0),
+            })
+            .collect();
+
+        let mut years = BTreeMap::<i32, YearCitations>::new();
+        for (m, citations) in self.metadata.iter().zip(citations_by_post.iter()) {
+            if let Some(date) = post_date(&m.header) {
+                let year = years.entry(date.year()).or_insert(YearCitations {
+                    year: date.year(),
+                    posts: 0,
+                    citations: 0,
+                });
+                year.posts += 1;
+                year.citations += citations;
+            }
+        }
+
+        let mut citations_per_post = self
+            .metadata
+            .iter()
+            .zip(citations_by_post.iter())
+            .map(|(m, citations)| PostCitations {
+                title: m.header.title.clone().unwrap_or("Unknown".to_string()),
+                url: m.url.clone(),
+                citations: *citations,
+            })
+            .collect::<Vec<PostCitations>>();
+        citations_per_post.sort_by_key(|p| Reverse(p.citations));
+
+        Statistics {
+            posts: self.metadata.len(),
+            citations: citations_by_post.iter().sum(),
+            books: most_cited(books, limit),
+            chapters: most_cited(chapters, limit),
+            verses: most_cited(verses, limit),
+            testaments,
+            citations_per_post,
+            years: years.into_values().collect(),
+            discussed: most_cited(discussed, limit),
+        }
+    }
+}
+
+impl Statistics {
+    /// write the statistics as a series of tables
+    pub fn write_tables(&self, mut f: impl Write) -> io::Result<()> {
+        writeln!(f, "{} posts, {} citations", self.posts, self.citations)?;
+
+        for (heading, cited) in [
+            ("Most cited books", &self.books),
+            ("Most cited chapters", &self.chapters),
+            ("Most cited verses", &self.verses),
+            ("Most discussed passages", &self.discussed),
+        ] {
+            writeln!(f, "\n{}\n", heading)?;
+            write_table(
+                &mut f,
+                ["", "Posts"],
+                cited
+                    .iter()
+                    .map(|c| [c.passage.clone(), c.posts.to_string()]),
+            )?;
+        }
+
+        writeln!(f, "\nCitations per testament\n")?;
+        write_table(
+            &mut f,
+            ["", "Posts", "Citations"],
+            self.testaments.iter().map(|t| {
+                [
+                    t.testament.to_string(),
+                    t.posts.to_string(),
+                    t.citations.to_string(),
+                ]
+            }),
+        )?;
+
+        writeln!(f, "\nCitations per year\n")?;
+        write_table(
+            &mut f,
+            ["", "Posts", "Citations"],
+            self.years.iter().map(|y| {
+                [
+                    y.year.to_string(),
+                    y.posts.to_string(),
+                    y.citations.to_string(),
+                ]
+            }),
+        )?;
+
+        writeln!(f, "\nCitations per post\n")?;
+        write_table(
+            &mut f,
+            ["", "", "Citations"],
+            self.citations_per_post
+                .iter()
+                .map(|p| [p.title.clone(), p.url.clone(), p.citations.to_string()]),
+        )?;
+
+        Ok(())
+    }
+}
+
+mod tests;
@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use super::*;
+use crate::bible::all_references;
+
+fn cited(cited: &[Cited]) -> Vec<(&str, usize)> {
+    cited
+        .iter()
+        .map(|c| (c.passage.as_str(), c.posts))
+        .collect()
+}
+
+fn statistics() -> Statistics {
+    all_references(&[
+        (
+            Some("2022-03-01T06:00:00Z"),
+            "Romans 8:28 and Genesis 1:1-2",
+        ),
+        (
+            Some("2023-03-01T06:00:00Z"),
+            "Romans 8:28-30 and Romans 12:1",
+        ),
+        (Some("2023-06-01T06:00:00Z"), "Jude v3"),
+        (None, "no references here"),
+    ])
+    .statistics(Style::plain(), 2)
+}
+
+#[test]
+fn test_statistics_most_cited() {
+    let s = statistics();
+
+    assert_eq!((s.posts, s.citations), (4, 5));
+    assert_eq!(cited(&s.books), vec![("Romans", 2), ("Genesis", 1)]);
+    assert_eq!(cited(&s.chapters), vec![("Romans 8", 2), ("Genesis 1", 1)]);
+    assert_eq!(
+        cited(&s.verses),
+        vec![("Romans 8:28", 2), ("Genesis 1:1", 1)]
+    );
+    assert_eq!(
+        cited(&s.discussed),
+        vec![("Romans 8:28", 2), ("Romans 8:28-30", 2)]
+    );
+}
+
+#[test]
+fn test_statistics_per_testament_post_and_year() {
+    let s = statistics();
+
+    assert_eq!(
+        s.testaments
+            .iter()
+            .map(|t| (t.testament, t.posts, t.citations))
+            .collect::<Vec<_>>(),
+        vec![("Old Testament", 1, 1), ("New Testament", 3, 4)]
+    );
+    assert_eq!(
+        s.citations_per_post
+            .iter()
+            .map(|p| (p.url.as_str(), p.citations))
+            .collect::<Vec<_>>(),
+        vec![
+            ("/post/0.md", 2),
+            ("/post/1.md", 2),
+            ("/post/2.md", 1),
+            ("/post/3.md", 0)
+        ]
+    );
+    assert_eq!(
+        s.years
+            .iter()
+            .map(|y| (y.year, y.posts, y.citations))
+            .collect::<Vec<_>>(),
+        vec![(2022, 1, 2), (2023, 2, 3)]
+    );
+}
+
+#[test]
+fn test_statistics_json() {
+    let s = all_references(&[(None, "Jude v3")]).statistics(Style::plain(), 10);
+
+    assert_eq!(
+        serde_json::to_string(&s).unwrap(),
+        r#"{"posts":1,"citations":1,"books":[{"passage":"Jude","posts":1}],"chapters":[],"verses":[{"passage":"Jude v3","posts":1}],"testaments":[{"testament":"Old Testament","posts":0,"citations":0},{"testament":"New Testament","posts":1,"citations":1}],"citations_per_post":[{"title":"Post 0","url":"/post/0.md","citations":1}],"years":[],"discussed":[]}"#
+    );
+}
@@ -0,0 +1,206 @@
+use super::books::{abbreviation, sbl_abbreviation};
+use super::*;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+/// how book names are written in a citation
+#[derive(Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum BookStyle {
+    Full,
+    /// our own short abbreviations, e.g. Is, Ezk
+    Abbreviation,
+    /// SBL Handbook of Style abbreviations, e.g. Isa, Ezek
+    Sbl,
+}
+
+/// named citation styles
+#[derive(Deserialize, clap::ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// ASCII only, as in 1 Corinthians 4:17,19-20; 5:1
+    Plain,
+    /// the look of our scripture index, with hair spaces and chapters on separate lines
+    House,
+    /// full names and en-dash ranges, as in 1 Corinthians 4:17, 19–20; 5:1
+    Chicago,
+    /// SBL abbreviations and en-dash ranges, as in 1 Cor 4:17, 19–20; 5:1
+    Sbl,
+}
+
+/// house style for writing citations
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Style {
+    pub book: BookStyle,
+    /// between the ends of a verse range
+    pub range: String,
+    /// between verses or verse ranges, e.g. with a thin or hair space
+    pub separator: String,
+    /// between chapters
+    pub chapter_separator: String,
+}
+
+impl Style {
+    pub fn preset(preset: Preset) -> Self {
+        use Preset::*;
+
+        let (book, range, separator, chapter_separator) = match preset {
+            Plain => (BookStyle::Full, "-", ",", "; "),
+            House => (BookStyle::Abbreviation, "-", ",\u{200A}", " <br/> "),
+            Chicago => (BookStyle::Full, "–", ", ", "; "),
+            Sbl => (BookStyle::Sbl, "–", ", ", "; "),
+        };
+
+        Style {
+            book,
+            range: range.to_string(),
+            separator: separator.to_string(),
+            chapter_separator: chapter_separator.to_string(),
+        }
+    }
+
+    /// the style used by `Display`
+    pub fn plain() -> &'static Style {
+        lazy_static! {
+            static ref PLAIN: Style = Style::preset(Preset::Plain);
+        }
+
+        &PLAIN
+    }
+
+    pub fn book_name(&self, book: &'static str) -> &'static str {
+        match self.book {
+            BookStyle::Full => book,
+            BookStyle::Abbreviation => abbreviation(book).unwrap_or(book),
+            BookStyle::Sbl => sbl_abbreviation(book).unwrap_or(book),
+        }
+    }
+
+    /// rewrite verses as written in the text, keeping each verse exactly as written
+    pub fn verses(&self, text: &str) -> String {
+        text.split(',')
+            .map(|vspan| {
+                vspan
+                    .split(['-', '–'])
+                    .map(|v| v.trim())
+                    .collect::<Vec<&str>>()
+                    .join(&self.range)
+            })
+            .filter(|vspan| !vspan.is_empty())
+            .collect::<Vec<String>>()
+            .join(&self.separator)
+    }
+}
+
+/// a style as configured, where any field overrides the preset
+#[derive(Deserialize, PartialEq, Eq, Clone, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    pub preset: Option<Preset>,
+    pub book: Option<BookStyle>,
+    pub range: Option<String>,
+    pub separator: Option<String>,
+    pub chapter_separator: Option<String>,
+}
+
+impl StyleConfig {
+    /// resolve into a style, where a preset given explicitly takes precedence over the configured one
+    pub fn resolve(&self, explicit_preset: Option<Preset>, default_preset: Preset) -> Style {
+        let preset = explicit_preset.or(self.preset).unwrap_or(default_preset);
+        let style = Style::preset(preset);
+
+        Style {
+            book: self.book.unwrap_or(style.book),
+            range: self.range.clone().unwrap_or(style.range),
+            separator: self.separator.clone().unwrap_or(style.separator),
+            chapter_separator: self
+                .chapter_separator
+                .clone()
+                .unwrap_or(style.chapter_separator),
+        }
+    }
+}
+
+/// something which may be written as a citation in a given style
+pub trait Cite {
+    fn cite(&self, f: &mut Formatter<'_>, style: &Style) -> Result<(), fmt::Error>;
+
+    fn styled<'a>(&'a self, style: &'a Style) -> Styled<'a, Self>
+    where
+        Self: Sized,
+    {
+        Styled(self, style)
+    }
+}
+
+/// adapter for displaying a citation in a given style
+pub struct Styled<'a, T>(&'a T, &'a Style);
+
+impl<'a, T> Display for Styled<'a, T>
+where
+    T: Cite,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        self.0.cite(f, self.1)
+    }
+}
+
+impl Cite for VSpan {
+    fn cite(&self, f: &mut Formatter<'_>, style: &Style) -> Result<(), fmt::Error> {
+        use VSpan::*;
+        match self {
+            Point(x) => write!(f, "{}", x),
+            Line(x1, x2) => write!(f, "{}{}{}", x1, style.range, x2),
+        }
+    }
+}
+
+impl Cite for VSpans {
+    fn cite(&self, f: &mut Formatter<'_>, style: &Style) -> Result<(), fmt::Error> {
+        for (i, vspan) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(&style.separator)?;
+            }
+            vspan.cite(f, style)?;
+        }
+        Ok(())
+    }
+}
+
+impl Cite for ChapterVerses {
+    // we really do want to write out a warning to stderr here so:
+    #[allow(clippy::print_in_format_impl)]
+    fn cite(&self, f: &mut Formatter<'_>, style: &Style) -> Result<(), fmt::Error> {
+        match self.chapter {
+            Some(chapter) => {
+                if self.verses.is_empty() {
+                    write!(f, "{}", chapter)
+                } else {
+                    write!(f, "{}:{}", chapter, self.verses.styled(style))
+                }
+            }
+            None => {
+                if self.verses.is_empty() {
+                    eprintln!("WARNING: no chapter or verses for ChapterVerses::fmt");
+                    Ok(())
+                } else {
+                    write!(f, "v{}", self.verses.styled(style))
+                }
+            }
+        }
+    }
+}
+
+impl Cite for ChaptersVerses {
+    fn cite(&self, f: &mut Formatter<'_>, style: &Style) -> Result<(), fmt::Error> {
+        for (i, cv) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(&style.chapter_separator)?;
+            }
+            cv.cite(f, style)?;
+        }
+        Ok(())
+    }
+}
+
+mod tests;
@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+#[test_case("1-3", "1–3")]
+#[test_case(" 13 - 17 , 19", "13–17, 19")]
+#[test_case("6b,9a", "6b, 9a")]
+#[test_case("16, 18–19,", "16, 18–19")]
+fn test_style_verses(src: &str, expected: &str) {
+    assert_eq!(Style::preset(Preset::Chicago).verses(src), expected);
+}
+
+#[test_case(Preset::Plain, "1 Corinthians", "1 Corinthians")]
+#[test_case(Preset::House, "Isaiah", "Is")]
+#[test_case(Preset::Sbl, "Isaiah", "Isa")]
+#[test_case(Preset::Sbl, "Mark", "Mark")]
+fn test_style_book_name(preset: Preset, book: &'static str, expected: &str) {
+    assert_eq!(Style::preset(preset).book_name(book), expected);
+}
+
+#[test_case(Preset::Plain, "1 Cor 4:17, 19-20; 1 Cor 5:1", "4:17,19-20; 5:1")]
+#[test_case(
+    Preset::House,
+    "1 Cor 4:17, 19-20; 1 Cor 5:1",
+    "4:17,\u{200A}19-20 <br/> 5:1"
+)]
+#[test_case(Preset::Chicago, "1 Cor 4:17, 19-20; 1 Cor 5:1", "4:17, 19–20; 5:1")]
+#[test_case(Preset::Sbl, "Jude v3-5", "v3–5")]
+fn test_styled(preset: Preset, src: &str, expected: &str) {
+    let (refs, _) = references("", src);
+    let (_, cvs) = refs.into_iter().next().unwrap();
+
+    assert_eq!(cvs.styled(&Style::preset(preset)).to_string(), expected);
+}
+
+#[test]
+fn test_style_config_resolve() {
+    let config = StyleConfig {
+        preset: Some(Preset::Sbl),
+        separator: Some(",\u{2009}".to_string()),
+        ..StyleConfig::default()
+    };
+
+    assert_eq!(
+        config.resolve(None, Preset::House),
+        Style {
+            separator: ",\u{2009}".to_string(),
+            ..Style::preset(Preset::Sbl)
+        }
+    );
+    assert_eq!(
+        config.resolve(Some(Preset::Plain), Preset::House).book,
+        BookStyle::Full
+    );
+    assert_eq!(
+        StyleConfig::default().resolve(None, Preset::House),
+        Style::preset(Preset::House)
+    );
+}
@@ -1,27 +1,49 @@
-use super::{books::Testament, AllReferences, ChapterVerses, ChaptersVerses, References};
+use super::{
+    books::{is_single_chapter_book, Testament},
+    coverage::{write_heatmap, BookCoverage},
+    parallels::Parallels,
+    pericopes::Pericopes,
+    style::{Cite, Style},
+    text::Excerpts,
+    AllReferences, Chapter, ChapterVerses, ChaptersVerses, References, VSpans,
+};
 use crate::hugo::{format_href, write_table, Create, Header, Metadata};
-use crate::util::insert_in_order;
+use crate::util::slice_cmp;
 use anyhow::Result;
 use itertools::Itertools;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::{
     cmp::Ordering,
-    collections::{hash_map, HashMap},
-    fmt::{self, Display, Formatter},
-    io::{self, Write},
+    collections::{hash_map, HashMap, HashSet},
+    io::Write,
+    ops::Bound::Excluded,
 };
-use time::{format_description::well_known::iso8601::Iso8601, OffsetDateTime};
+use time::{format_description::well_known::iso8601::Iso8601, Date, OffsetDateTime};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 // a post with just one chapters worth of references
 pub struct PostReferences1 {
     pub post_index: usize,
     pub cv: ChapterVerses,
+    // referenced only as a parallel of a passage the post cites
+    pub via_parallel: bool,
 }
 
 impl PostReferences1 {
     pub fn new(post_index: usize, cv: ChapterVerses) -> Self {
-        Self { post_index, cv }
+        Self {
+            post_index,
+            cv,
+            via_parallel: false,
+        }
+    }
+
+    pub fn parallel(post_index: usize, cv: ChapterVerses) -> Self {
+        Self {
+            post_index,
+            cv,
+            via_parallel: true,
+        }
     }
 }
 
@@ -35,7 +57,10 @@ impl Ord for PostReferences1 {
     fn cmp(&self, other: &Self) -> Ordering {
         use Ordering::*;
         match self.cv.cmp(&other.cv) {
-            Equal => self.post_index.cmp(&other.post_index),
+            Equal => match self.post_index.cmp(&other.post_index) {
+                Equal => self.via_parallel.cmp(&other.via_parallel),
+                cmp => cmp,
+            },
             cmp => cmp,
         }
     }
@@ -46,12 +71,7 @@ impl Ord for PostReferences1 {
 pub struct PostReferences {
     pub post_index: usize,
     pub cvs: ChaptersVerses,
-}
-
-impl PostReferences {
-    fn push(&mut self, refs1: PostReferences1) {
-        self.cvs.0.push(refs1.cv);
-    }
+    pub via_parallel: bool,
 }
 
 impl From<PostReferences1> for PostReferences {
@@ -59,33 +79,46 @@ impl From<PostReferences1> for PostReferences {
         Self {
             post_index: refs1.post_index,
             cvs: ChaptersVerses::new(refs1.cv),
+            via_parallel: refs1.via_parallel,
         }
     }
 }
 
-impl Display for PostReferences {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{:#}", &self.cvs)
+impl PartialOrd for PostReferences {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl PartialOrd for PostReferences {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        use Ordering::*;
-        match self.cvs.partial_cmp(&other.cvs) {
-            Some(Equal) => Some(self.post_index.cmp(&other.post_index)),
-            cmp => cmp,
-        }
+impl Ord for PostReferences {
+    fn cmp(&self, other: &Self) -> Ordering {
+        slice_cmp(&self.cvs.0, &other.cvs.0)
+            .then(self.post_index.cmp(&other.post_index))
+            .then(self.via_parallel.cmp(&other.via_parallel))
     }
 }
 
-// separated references to a single book, non-empty
+// separated references to a single book, non-empty, in canonical order
 #[derive(Debug)]
-pub struct BookReferences1(Vec<PostReferences1>);
+pub struct BookReferences1(BTreeSet<PostReferences1>);
 
 impl BookReferences1 {
-    pub fn new(post_index: usize, cv: ChapterVerses) -> BookReferences1 {
-        BookReferences1(vec![PostReferences1::new(post_index, cv)])
+    pub fn new(r1: PostReferences1) -> BookReferences1 {
+        BookReferences1(BTreeSet::from([r1]))
+    }
+
+    pub fn iter(&self) -> std::collections::btree_set::Iter<'_, PostReferences1> {
+        self.0.iter()
+    }
+
+    /// references in the same chapter as the given one, found without scanning the whole book
+    fn in_chapter<'a>(&'a self, cv: &ChapterVerses) -> impl Iterator<Item = &'a PostReferences1> {
+        let chapter = cv.chapter;
+        let chapter_start = PostReferences1::new(0, ChapterVerses::new(chapter, VSpans::new()));
+
+        self.0
+            .range(chapter_start..)
+            .take_while(move |r1| r1.cv.chapter == chapter)
     }
 }
 
@@ -93,84 +126,81 @@ impl BookReferences1 {
 #[derive(Debug)]
 pub struct BookReferences(Vec<PostReferences>);
 
-// strategy for merging in a new reference
-enum MergeStrategy {
-    Append,
-    MoveAndMerge(usize, usize),
-}
-
 impl BookReferences {
-    fn new(p: PostReferences) -> BookReferences {
-        BookReferences(vec![p])
+    pub fn iter(&self) -> std::slice::Iter<'_, PostReferences> {
+        self.0.iter()
     }
 
-    fn latest_same_post(&self, post_index: usize) -> Option<usize> {
-        self.0
-            .iter()
-            .enumerate()
-            .rev()
-            .find(|(_i, r)| r.post_index == post_index)
-            .map(|(i, _r)| i)
-    }
-
-    fn merge_strategy(&self, r1: &PostReferences1) -> MergeStrategy {
-        use MergeStrategy::*;
-
-        match r1.cv.chapter {
-            // don't need to merge in books without chapters
-            None => Append,
-            Some(_) => {
-                if let Some(i_same_post) = self.latest_same_post(r1.post_index) {
-                    // see if we can maintain order by merging these
-
-                    // make a temporary candidate and test that
-                    let mut candidate = self.0[i_same_post].clone();
-                    candidate.push(r1.clone());
-
-                    let orderings = self.0[i_same_post + 1..]
-                        .iter()
-                        .map(|p| candidate.partial_cmp(p))
-                        .collect::<Vec<Option<Ordering>>>();
-
-                    if orderings.iter().all(|o| o.is_some()) {
-                        // can merge, yay!
-                        // so find where we have to move the previous post so we can merge
-                        match orderings
-                            .iter()
-                            .zip(i_same_post + 1..)
-                            .find(|(o, _)| **o == Some(Ordering::Less))
-                        {
-                            Some((_, i)) => MoveAndMerge(i_same_post, i),
-                            None => MoveAndMerge(i_same_post, self.0.len()),
-                        }
-                    } else {
-                        // nope
-                        Append
-                    }
-                } else {
-                    Append
-                }
+    /// Merge the references for each post into as few rows as possible, in canonical order.
+    ///
+    /// A row may span chapters lo..hi only where no other post cites a chapter strictly between them,
+    /// so reading down the page never passes over a citation which belongs within a merged row.
+    /// Any part of an allowed row is itself allowed, so extending each row as far as it will go
+    /// gives the fewest rows for every post.  The rule depends only on which chapters each post cites,
+    /// so the layout doesn't depend on the order of the separated references.
+    /// Each reference is checked only for the chapters since the post's previous reference, so each
+    /// post scans the chapters cited in the book at most once, and coalescing R references by P posts
+    /// to C chapters takes O(R log C + P C) time, where C is at most the book's chapter count.
+    ///
+    /// References via a parallel are kept apart from those cited directly by the same post.
+    fn from_separated(refs1: BookReferences1) -> BookReferences {
+        let mut cvs_by_post = BTreeMap::<(usize, bool), Vec<ChapterVerses>>::new();
+        let mut posts_by_chapter = BTreeMap::<Chapter, BTreeSet<(usize, bool)>>::new();
+
+        for r1 in refs1.0 {
+            let post = (r1.post_index, r1.via_parallel);
+            if let Some(chapter) = r1.cv.chapter {
+                posts_by_chapter.entry(chapter).or_default().insert(post);
             }
+            cvs_by_post.entry(post).or_default().push(r1.cv);
         }
-    }
 
-    fn from_separated(refs1: BookReferences1) -> BookReferences {
-        let mut it1 = refs1.0.into_iter();
-        let mut refs = BookReferences::new(PostReferences::from(it1.by_ref().next().unwrap())); // never empty
-        for r1 in it1 {
-            use MergeStrategy::*;
-
-            match refs.merge_strategy(&r1) {
-                Append => refs.0.push(PostReferences::from(r1)),
-                MoveAndMerge(i_src, i_dst) => {
-                    let p = refs.0.remove(i_src);
-                    refs.0.insert(i_dst - 1, p);
-                    refs.0[i_dst - 1].push(r1);
+        let cited_by_other = |post: &(usize, bool), chapter: Chapter| {
+            posts_by_chapter[&chapter].iter().any(|p| p != post)
+        };
+
+        let cited_by_other_between = |post: &(usize, bool), lo: Chapter, hi: Chapter| {
+            lo < hi
+                && posts_by_chapter
+                    .range((Excluded(lo), Excluded(hi)))
+                    .any(|(_, posts)| posts.iter().any(|p| p != post))
+        };
+
+        let mut rows = Vec::<PostReferences>::new();
+        for (post, cvs) in cvs_by_post {
+            // first and latest chapter of the current row for this post
+            let mut row_span: Option<(Chapter, Chapter)> = None;
+
+            for cv in cvs {
+                let extends_row = match (row_span, cv.chapter) {
+                    (Some((lo, latest)), Some(chapter)) => {
+                        // the row is clear up to the latest chapter, so only check from there
+                        let latest_inside = lo < latest && latest < chapter;
+                        !(latest_inside && cited_by_other(&post, latest)
+                            || cited_by_other_between(&post, latest, chapter))
+                    }
+                    _ => false,
+                };
+
+                row_span = match (row_span, cv.chapter) {
+                    (Some((lo, _)), Some(chapter)) if extends_row => Some((lo, chapter)),
+                    (_, chapter) => chapter.map(|chapter| (chapter, chapter)),
+                };
+
+                if extends_row {
+                    rows.last_mut().unwrap().cvs.0.push(cv);
+                } else {
+                    rows.push(PostReferences {
+                        post_index: post.0,
+                        cvs: ChaptersVerses::new(cv),
+                        via_parallel: post.1,
+                    });
                 }
             }
         }
 
-        refs
+        rows.sort();
+        BookReferences(rows)
     }
 }
 
@@ -180,66 +210,107 @@ impl AllReferences {
             metadata: Vec::new(),
             post_index_by_epoch: BTreeMap::new(),
             post_sequence_number_by_index: Vec::new(),
+            quoted_translations_by_index: Vec::new(),
             separated_refs_by_book: HashMap::new(),
             refs_by_book: HashMap::new(),
         }
     }
 
-    pub fn tabulate(&mut self, c: Box<dyn Create>, with_sequence_numbers: bool) -> Result<()> {
-        if with_sequence_numbers {
+    pub fn tabulate(&mut self, c: Box<dyn Create>, options: IndexOptions) -> Result<()> {
+        if options.with_sequence_numbers || options.order == RowOrder::Sequence {
             self.calculate_post_sequence_numbers();
         }
-        self.coelesce();
-        // useful for diagnostics:
-        //self.dump_repeats(io::stdout())?;
+        // coverage is of the separated references, which are consumed by coalescing
+        let coverage = options.with_coverage.then(|| self.coverage());
+        self.coalesce();
 
-        let mut w = Writer::new(c);
-        w.write_references(self, with_sequence_numbers)?;
+        let mut w = Writer::new(c, options);
+        w.write_references(self, coverage.as_deref())?;
 
         Ok(())
     }
 
-    // insert the post references separately and return a stable reference to its metadata
-    pub fn insert(&mut self, metadata: Metadata, refs: References) -> &Metadata {
+    // insert the post references separately, along with those cited by quotes which name their translation,
+    // and return a stable reference to its metadata
+    pub fn insert(
+        &mut self,
+        metadata: Metadata,
+        refs: References,
+        quoted_translations: Vec<(String, References)>,
+    ) -> &Metadata {
         self.metadata.push(metadata);
         self.post_sequence_number_by_index.push(None);
+        self.quoted_translations_by_index.push(quoted_translations);
 
         let post_index = self.metadata.len() - 1;
         let post_header = &self.metadata.last().unwrap().header;
 
-        if let Some(epoch) = post_header.date.as_ref().and_then(|date| {
-            OffsetDateTime::parse(date.as_str(), &Iso8601::DEFAULT)
-                .ok()
-                .map(|date| date.unix_timestamp())
-        }) {
+        if let Some(epoch) = post_date(post_header).map(|date| date.unix_timestamp()) {
             self.post_index_by_epoch.insert(epoch, post_index);
         }
 
         for (book, cvs) in refs.into_iter() {
             for cv in cvs.into_iter() {
-                use hash_map::Entry::*;
-                match self.separated_refs_by_book.entry(book) {
-                    Occupied(mut o) => {
-                        let br = o.get_mut();
-                        insert_in_order(&mut br.0, PostReferences1::new(post_index, cv));
-                    }
-                    Vacant(v) => {
-                        v.insert(BookReferences1::new(post_index, cv));
+                self.insert_separated(book, PostReferences1::new(post_index, cv));
+            }
+        }
+
+        self.metadata.last().unwrap() // always exists
+    }
+
+    fn insert_separated(&mut self, book: &'static str, r1: PostReferences1) {
+        use hash_map::Entry::*;
+        match self.separated_refs_by_book.entry(book) {
+            Occupied(mut o) => {
+                o.get_mut().0.insert(r1);
+            }
+            Vacant(v) => {
+                v.insert(BookReferences1::new(r1));
+            }
+        }
+    }
+
+    /// also reference each post from the parallels of the passages it cites,
+    /// except where the post already cites the parallel passage itself
+    pub fn insert_parallels(&mut self, parallels: &Parallels) {
+        let mut via_parallels = Vec::new();
+
+        for (book, refs1) in self.separated_refs_by_book.iter() {
+            for r1 in refs1.iter() {
+                for (parallel_book, parallel_cv) in parallels.parallels_of(book, &r1.cv) {
+                    let cited_directly = self
+                        .separated_refs_by_book
+                        .get(parallel_book)
+                        .map(|parallel_refs1| {
+                            parallel_refs1.in_chapter(parallel_cv).any(|p| {
+                                p.post_index == r1.post_index && p.cv.overlaps(parallel_cv)
+                            })
+                        })
+                        .unwrap_or(false);
+
+                    if !cited_directly {
+                        via_parallels.push((
+                            parallel_book,
+                            PostReferences1::parallel(r1.post_index, parallel_cv.clone()),
+                        ));
                     }
                 }
             }
         }
 
-        self.metadata.last().unwrap() // always exists
+        for (book, r1) in via_parallels {
+            self.insert_separated(book, r1);
+        }
     }
 
-    fn calculate_post_sequence_numbers(&mut self) {
+    pub fn calculate_post_sequence_numbers(&mut self) {
         for (i, (_, post_index)) in self.post_index_by_epoch.iter().enumerate() {
             self.post_sequence_number_by_index[*post_index] = Some(i + 1);
         }
     }
 
-    fn coelesce(&mut self) {
+    /// merge the separated references for each book into rows for the index
+    pub fn coalesce(&mut self) {
         self.refs_by_book = HashMap::<&str, BookReferences>::from_iter(
             self.separated_refs_by_book
                 .drain()
@@ -247,151 +318,610 @@ impl AllReferences {
         );
     }
 
-    pub fn _dump_repeats(&self, mut w: impl Write) -> io::Result<()> {
-        for testament in Testament::all() {
-            w.write_all(format!("{}\n", testament.name()).as_bytes())?;
-
-            for book in testament._books() {
-                if let Some(refs) = self.refs_by_book.get(book) {
-                    let mut post_count = HashMap::<usize, u8>::new();
-                    for r in refs.0.iter() {
-                        use hash_map::Entry::*;
-
-                        match post_count.entry(r.post_index) {
-                            Occupied(mut o) => {
-                                *o.get_mut() += 1;
-                            }
-                            Vacant(v) => {
-                                v.insert(1);
-                            }
-                        }
+    /// indices of posts which cite a passage overlapping the given one, possibly repeated
+    pub fn post_indices_citing<'a>(
+        &'a self,
+        book: &'static str,
+        cv: &'a ChapterVerses,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.separated_refs_by_book
+            .get(book)
+            .into_iter()
+            .flat_map(|refs1| refs1.in_chapter(cv))
+            .filter(move |r1| !r1.via_parallel && r1.cv.overlaps(cv))
+            .map(|r1| r1.post_index)
+    }
+
+    fn posts_by_index(&self, mut post_indices: Vec<usize>) -> Vec<&Metadata> {
+        post_indices.sort();
+        post_indices.dedup();
+
+        post_indices
+            .into_iter()
+            .map(|post_index| &self.metadata[post_index])
+            .collect()
+    }
+
+    /// posts which cite a passage overlapping the given one, in the order they were inserted
+    pub fn posts_citing(&self, book: &'static str, cv: &ChapterVerses) -> Vec<&Metadata> {
+        self.posts_by_index(self.post_indices_citing(book, cv).collect())
+    }
+
+    /// posts which cite a passage overlapping any of the references, in the order they were inserted
+    pub fn posts_citing_any<'a>(
+        &self,
+        refs: impl IntoIterator<Item = &'a References>,
+    ) -> Vec<&Metadata> {
+        let mut post_indices = Vec::new();
+
+        for refs in refs {
+            for (book, cvs) in refs.iter() {
+                for cv in cvs {
+                    post_indices.extend(self.post_indices_citing(book, cv));
+                }
+            }
+        }
+
+        self.posts_by_index(post_indices)
+    }
+
+    /// translations used by the post in citing the chapters and verses, being those named by its quotes
+    /// of each, or else the post's own translation
+    pub fn translations<'a>(
+        &self,
+        post_index: usize,
+        book: &'static str,
+        cvs: impl IntoIterator<Item = &'a ChapterVerses>,
+    ) -> Vec<&str> {
+        let mut translations = Vec::new();
+
+        // each verse span separately, since only some may be quoted
+        let spans = cvs.into_iter().flat_map(|cv| {
+            if cv.verses.is_empty() {
+                vec![cv.clone()]
+            } else {
+                cv.verses
+                    .into_iter()
+                    .map(|vspan| ChapterVerses::new(cv.chapter, VSpans::from_iter([vspan.clone()])))
+                    .collect()
+            }
+        });
+
+        for cv in spans {
+            let mut quoted_cv = false;
+
+            for (translation, refs) in self.quoted_translations_by_index[post_index].iter() {
+                if refs
+                    .iter()
+                    .filter(|(quoted_book, _)| **quoted_book == book)
+                    .flat_map(|(_, quoted_cvs)| quoted_cvs)
+                    .any(|quoted| quoted.overlaps(&cv))
+                {
+                    quoted_cv = true;
+                    if !translations.contains(&translation.as_str()) {
+                        translations.push(translation.as_str());
                     }
+                }
+            }
 
-                    let mut written_book = false;
-
-                    for (post_index, count) in post_count {
-                        if count > 1 {
-                            if !written_book {
-                                w.write_all(format!("    {}\n", book).as_bytes())?;
-                                written_book = true;
-                            }
-                            w.write_all(
-                                format!(
-                                    "            {} \"{}\"\n",
-                                    count,
-                                    self.metadata[post_index]
-                                        .header
-                                        .title
-                                        .as_ref()
-                                        .unwrap_or(&"UNTITLED".to_string())
-                                )
-                                .as_bytes(),
-                            )?
-                        }
+            if !quoted_cv {
+                if let Some(translation) = &self.metadata[post_index].header.translation {
+                    if !translations.contains(&translation.as_str()) {
+                        translations.push(translation.as_str());
                     }
                 }
             }
         }
-        Ok(())
+
+        translations
+    }
+}
+
+/// posts with no quoted translations
+impl FromIterator<(Metadata, References)> for AllReferences {
+    fn from_iter<I>(posts: I) -> Self
+    where
+        I: IntoIterator<Item = (Metadata, References)>,
+    {
+        let mut all = AllReferences::new();
+        for (metadata, refs) in posts {
+            all.insert(metadata, refs, Vec::new());
+        }
+
+        all
     }
 }
 
-pub struct Writer {
+/// the anchor for the chapter heading on a book page
+fn chapter_anchor(chapter: Chapter) -> String {
+    format!("chapter-{}", chapter)
+}
+
+/// the anchor for the start of each span of verses, or for the chapter if it is cited whole
+fn cv_anchors(cv: &ChapterVerses) -> Vec<String> {
+    if cv.verses.is_empty() {
+        return cv.chapter.map(chapter_anchor).into_iter().collect();
+    }
+
+    cv.verses
+        .into_iter()
+        .map(|vspan| match cv.chapter {
+            Some(chapter) => format!("v{}-{}", chapter, vspan.lower()),
+            None => format!("v{}", vspan.lower()),
+        })
+        .collect()
+}
+
+/// the anchor for the book's section on the single page index
+fn book_anchor(book: &str) -> String {
+    slug::slugify(book)
+}
+
+/// the anchor, made unique on a page shared by all books if the book has an anchor there
+fn anchor_within(book_anchor: Option<&str>, anchor: String) -> String {
+    match book_anchor {
+        Some(book_anchor) => format!("{}-{}", book_anchor, anchor),
+        None => anchor,
+    }
+}
+
+/// text made safe for an HTML table cell in Markdown
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('|', "&#124;")
+}
+
+/// how rows are ordered on a book page
+#[derive(clap::ValueEnum, PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum RowOrder {
+    /// canonical order, with repeated references to a post merged into a single row
+    #[default]
+    Canonical,
+    /// oldest post first
+    Date,
+    /// by post sequence number
+    Sequence,
+    /// by post title
+    Title,
+}
+
+/// where the whole index is written when it's all on one page
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SinglePage {
+    /// the section's own index page
+    Branch,
+    /// a page with this title, linked from the section's index page
+    Leaf(String),
+}
+
+/// the date of the post, if it has one we understand, where a date without a time is taken as
+/// midnight UTC
+pub fn post_date(header: &Header) -> Option<OffsetDateTime> {
+    let date = header.date.as_ref()?;
+    OffsetDateTime::parse(date, &Iso8601::DEFAULT)
+        .or_else(|_| Date::parse(date, &Iso8601::DEFAULT).map(|date| date.midnight().assume_utc()))
+        .ok()
+}
+
+/// what to include in the scripture index, and how
+pub struct IndexOptions<'a> {
+    pub style: Style,
+    pub pericopes: &'a Pericopes,
+    pub excerpts: Option<&'a Excerpts>,
+    pub with_sequence_numbers: bool,
+    pub with_translations: bool,
+    /// books with more rows than this are a section, with a page for each chapter
+    pub chapter_pages_over: Option<usize>,
+    pub order: RowOrder,
+    pub with_dates: bool,
+    /// all books on one page, in which case there are no chapter pages
+    pub single_page: Option<SinglePage>,
+    /// a heatmap of how much of each book and chapter is cited, on the index page
+    pub with_coverage: bool,
+}
+
+pub struct Writer<'a> {
     c: Box<dyn Create>,
+    options: IndexOptions<'a>,
 }
 
-impl Writer {
-    pub fn new(c: Box<dyn Create>) -> Self {
-        Writer { c }
+impl<'a> Writer<'a> {
+    pub fn new(c: Box<dyn Create>, options: IndexOptions<'a>) -> Self {
+        Writer { c, options }
+    }
+
+    const BOOK_REFS_DESCRIPTION: &'static str = "Scripture index";
+
+    const VIA_PARALLEL: &'static str = r###"<span style="font-size:smaller; font-style:italic; padding-left:0.5em;">via parallel</span>"###;
+
+    const EXCERPT_SPAN: &'static str = r###"<span style="font-size:smaller;">"###;
+
+    const TRANSLATION_SPAN: &'static str =
+        r###"<span style="font-size:smaller; padding-left:0.5em;">"###;
+
+    const BOOK_ROW_SIZE: usize = 4;
+
+    const CHAPTER_ROW_SIZE: usize = 10;
+
+    /// the rows in the required order, where rows for the same post remain in canonical order
+    fn ordered<'r>(
+        &self,
+        refs: &'r [PostReferences],
+        posts: &AllReferences,
+    ) -> Vec<&'r PostReferences> {
+        let mut refs = refs.iter().collect::<Vec<&PostReferences>>();
+
+        match self.options.order {
+            RowOrder::Canonical => (),
+            RowOrder::Date => refs.sort_by_key(|r| {
+                // undated posts last
+                let date = post_date(&posts.metadata[r.post_index].header);
+                (date.is_none(), date)
+            }),
+            RowOrder::Sequence => refs.sort_by_key(|r| {
+                let sequence_number = posts.post_sequence_number_by_index[r.post_index];
+                (sequence_number.is_none(), sequence_number)
+            }),
+            RowOrder::Title => refs.sort_by_cached_key(|r| {
+                posts.metadata[r.post_index]
+                    .header
+                    .title
+                    .as_ref()
+                    .map(|title| title.to_lowercase())
+            }),
+        }
+
+        refs
+    }
+
+    /// the table rows for the references, where each verse or whole chapter cited is anchored in
+    /// the first row citing it, unless already anchored on the page, as by a chapter heading
+    fn rows(
+        &self,
+        book: &'static str,
+        book_anchor: Option<&str>,
+        refs: &[&PostReferences],
+        posts: &AllReferences,
+        mut anchors: HashSet<String>,
+    ) -> anyhow::Result<Vec<Vec<String>>> {
+        let options = &self.options;
+        refs.iter()
+            .map(|r| -> anyhow::Result<Vec<String>> {
+                let m = &posts.metadata[r.post_index];
+                let sequence_number = &posts.post_sequence_number_by_index[r.post_index];
+                let mut href = m.format_href(if options.with_sequence_numbers {
+                    sequence_number
+                } else {
+                    &None
+                });
+                if options.with_translations {
+                    let translations = posts.translations(r.post_index, book, &r.cvs);
+                    if !translations.is_empty() {
+                        href = format!(
+                            "{}{}({})</span>",
+                            href,
+                            Self::TRANSLATION_SPAN,
+                            translations.join(", ")
+                        );
+                    }
+                }
+                if r.via_parallel {
+                    href = format!("{}{}", href, Self::VIA_PARALLEL);
+                }
+
+                let titles = options.pericopes.titles(book, &r.cvs);
+                let mut cited = if titles.is_empty() {
+                    r.cvs.styled(&options.style).to_string()
+                } else {
+                    format!("{} — {}", r.cvs.styled(&options.style), titles.join("; "))
+                };
+                if let Some(excerpts) = options.excerpts {
+                    let excerpt = excerpts.excerpt(book, &r.cvs)?;
+                    if !excerpt.is_empty() {
+                        cited = format!(
+                            "{}<br/>{}{}</span>",
+                            cited,
+                            Self::EXCERPT_SPAN,
+                            escape_html(&excerpt)
+                        );
+                    }
+                }
+
+                let spans = r
+                    .cvs
+                    .0
+                    .iter()
+                    .flat_map(cv_anchors)
+                    .map(|anchor| anchor_within(book_anchor, anchor))
+                    .filter(|anchor| anchors.insert(anchor.clone()))
+                    .map(|anchor| format!(r#"<span id="{}"></span>"#, anchor))
+                    .collect::<String>();
+                cited = format!("{}{}", spans, cited);
+
+                let mut row = vec![cited, href];
+                if options.with_dates {
+                    row.push(
+                        post_date(&m.header)
+                            .map(|date| date.date().to_string())
+                            .unwrap_or_default(),
+                    );
+                }
+
+                Ok(row)
+            })
+            .collect()
     }
 
-    const BOOK_REFS_DESCRIPTION: &str = "Scripture index";
+    /// write the tables of references, with the rows grouped under a heading for each chapter
+    /// if required, where rows which span chapters are under the first, and where the book has
+    /// an anchor if it shares the page with other books
+    fn write_refs_tables(
+        &self,
+        mut f: impl Write,
+        book: &'static str,
+        book_anchor: Option<&str>,
+        refs: &[&PostReferences],
+        posts: &AllReferences,
+        with_chapter_headings: bool,
+    ) -> anyhow::Result<()> {
+        let first_chapter = |r: &&PostReferences| {
+            if with_chapter_headings {
+                r.cvs.0.first().and_then(|cv| cv.chapter)
+            } else {
+                None
+            }
+        };
+        let mut refs = refs.to_vec();
+        refs.sort_by_key(first_chapter);
+
+        let heading_anchors = refs
+            .iter()
+            .filter_map(first_chapter)
+            .map(|chapter| anchor_within(book_anchor, chapter_anchor(chapter)))
+            .collect::<HashSet<String>>();
+        let rows = self.rows(book, book_anchor, &refs, posts, heading_anchors)?;
+        let chapter_heading_level = if book_anchor.is_some() { "####" } else { "##" };
+        for (chapter, group) in &refs.iter().zip(rows).group_by(|(r, _)| first_chapter(r)) {
+            match chapter {
+                Some(chapter) => f.write_all(
+                    format!(
+                        "\n{} Chapter {} {{#{}}}\n\n",
+                        chapter_heading_level,
+                        chapter,
+                        anchor_within(book_anchor, chapter_anchor(chapter))
+                    )
+                    .as_bytes(),
+                )?,
+                None => f.write_all("\n".as_bytes())?,
+            }
+
+            let heading = if self.options.with_dates {
+                vec!["", "", ""]
+            } else {
+                vec!["", ""]
+            };
+            write_table(&mut f, heading, group.map(|(_, row)| row))?;
+        }
+
+        Ok(())
+    }
 
+    /// write a page of references and return its URL
+    fn write_refs_page(
+        &self,
+        c: &mut dyn Create,
+        title: &str,
+        book: &'static str,
+        refs: &[&PostReferences],
+        posts: &AllReferences,
+        with_chapter_headings: bool,
+    ) -> anyhow::Result<String> {
+        let h = Header::new(title, Self::BOOK_REFS_DESCRIPTION);
+        let (mut f, url) = c.create_leaf(&h)?;
+        self.write_refs_tables(&mut f, book, None, refs, posts, with_chapter_headings)?;
+
+        f.flush()?;
+        Ok(url)
+    }
+
+    /// write the references for the book, either all on the one page, or with the book as a section
+    /// with a page for each chapter and its branch page linking to these, and return the link to
+    /// the book page
     fn write_book_refs(
         &mut self,
-        book: &str,
+        book: &'static str,
         abbrev: &str,
         refs: &[PostReferences],
         posts: &AllReferences,
-        with_sequence_numbers: bool,
     ) -> anyhow::Result<String> {
+        let refs = self.ordered(refs, posts);
+        let chapter_pages = match self.options.chapter_pages_over {
+            Some(max_rows) => refs.len() > max_rows && !is_single_chapter_book(book),
+            None => false,
+        };
+
         let h = Header::new(book, Self::BOOK_REFS_DESCRIPTION);
-        self.c.create_leaf(&h).and_then(|(mut f, url)| {
-            f.write_all("\n".as_bytes())?;
+        if !chapter_pages {
+            let with_chapter_headings = self.options.order == RowOrder::Canonical;
+            let (mut f, url) = self.c.create_leaf(&h)?;
+            self.write_refs_tables(&mut f, book, None, &refs, posts, with_chapter_headings)?;
+
+            f.flush()?;
+            return Ok(format_href(abbrev, &url));
+        }
+
+        let (mut c, url) = self.c.create_section(&h)?;
+
+        let chapters = refs
+            .iter()
+            .flat_map(|r| r.cvs.0.iter().filter_map(|cv| cv.chapter))
+            .collect::<BTreeSet<Chapter>>();
 
-            let heading = vec!["", ""];
-            let body = refs
+        let mut hrefs = Vec::new();
+        for chapter in chapters {
+            // rows spanning several chapters appear on the page for each
+            let chapter_refs = refs
                 .iter()
-                .map(|r| {
-                    let m = &posts.metadata[r.post_index];
-                    let sequence_number = &posts.post_sequence_number_by_index[r.post_index];
-                    vec![
-                        r.to_string(),
-                        m.format_href(if with_sequence_numbers {
-                            sequence_number
-                        } else {
-                            &None
-                        }),
-                    ]
-                })
-                .collect::<Vec<Vec<String>>>();
+                .filter(|r| r.cvs.0.iter().any(|cv| cv.chapter == Some(chapter)))
+                .copied()
+                .collect::<Vec<&PostReferences>>();
+            let title = format!("{} {}", book, chapter);
+            let url =
+                self.write_refs_page(c.as_mut(), &title, book, &chapter_refs, posts, false)?;
+            // as on the book page without chapter pages
+            hrefs.push(format!(
+                r#"<span id="{}"></span>{}"#,
+                chapter_anchor(chapter),
+                format_href(&chapter.to_string(), &url)
+            ));
+        }
 
-            write_table(&f, heading, body)?;
+        let mut f = c.create_branch()?;
+        self.write_grid(&mut f, "Chapters", &hrefs, Self::CHAPTER_ROW_SIZE)?;
 
-            f.flush()?;
-            Ok(format_href(abbrev, &url))
-        })
+        f.flush()?;
+        Ok(format_href(abbrev, &url))
     }
 
     fn write_refs(
         &mut self,
-        book_abbrev_iter: impl Iterator<Item = (&'static str, &'static str)>,
+        book_iter: impl Iterator<Item = &'static str>,
         hrefs: &mut Vec<String>,
         posts: &AllReferences,
-        with_sequence_numbers: bool,
     ) -> anyhow::Result<()> {
-        for (book, abbrev) in book_abbrev_iter {
+        for book in book_iter {
             if let Some(refs) = posts.refs_by_book.get(book) {
-                let href =
-                    self.write_book_refs(book, abbrev, &refs.0, posts, with_sequence_numbers)?;
+                let abbrev = self.options.style.book_name(book);
+                let href = self.write_book_refs(book, abbrev, &refs.0, posts)?;
                 hrefs.push(href);
             }
         }
         Ok(())
     }
 
+    fn write_coverage(&self, mut f: impl Write, coverage: &[BookCoverage]) -> anyhow::Result<()> {
+        for testament in Testament::all() {
+            let books = testament.books().collect::<HashSet<&'static str>>();
+            write_heatmap(
+                &mut f,
+                &format!("{} coverage", testament.name()),
+                coverage.iter().filter(|b| books.contains(b.book)),
+                &self.options.style,
+            )?;
+        }
+
+        Ok(())
+    }
+
     fn write_grid(
         &mut self,
         mut f: impl Write,
         heading: &str,
         hrefs: &[String],
+        row_size: usize,
     ) -> anyhow::Result<()> {
         f.write_all(format!("\n**{}**\n", heading).as_bytes())?;
 
-        const ROW_SIZE: usize = 4;
-        let header = std::iter::repeat("").take(ROW_SIZE);
+        let header = std::iter::repeat_n("", row_size);
 
-        write_table(&mut f, header, &hrefs.iter().chunks(ROW_SIZE))?;
+        write_table(&mut f, header, &hrefs.iter().chunks(row_size))?;
 
         Ok(())
     }
 
+    /// write all the books on the one page, grouped by testament, after a jump table of books
+    fn write_single_page(
+        &mut self,
+        single_page: &SinglePage,
+        posts: &AllReferences,
+        coverage: Option<&[BookCoverage]>,
+    ) -> anyhow::Result<()> {
+        let mut f = match single_page {
+            SinglePage::Branch => self.c.create_branch()?,
+            SinglePage::Leaf(title) => {
+                let h = Header::new(title, Self::BOOK_REFS_DESCRIPTION);
+                let (f, url) = self.c.create_leaf(&h)?;
+
+                let mut branch = self.c.create_branch()?;
+                branch.write_all(format!("\n{}\n", format_href(title, &url)).as_bytes())?;
+
+                f
+            }
+        };
+
+        let books_by_testament = Testament::all()
+            .map(|testament| {
+                let books = testament
+                    .books()
+                    .filter(|book| posts.refs_by_book.contains_key(book))
+                    .collect::<Vec<&'static str>>();
+                (testament, books)
+            })
+            .collect::<Vec<(Testament, Vec<&'static str>)>>();
+
+        for (testament, books) in books_by_testament.iter() {
+            let hrefs = books
+                .iter()
+                .map(|book| {
+                    format!(
+                        "[{}](#{})",
+                        self.options.style.book_name(book),
+                        book_anchor(book)
+                    )
+                })
+                .collect::<Vec<String>>();
+            self.write_grid(&mut f, testament.name(), &hrefs, Self::BOOK_ROW_SIZE)?;
+        }
+        if let Some(coverage) = coverage {
+            self.write_coverage(&mut f, coverage)?;
+        }
+
+        let with_chapter_headings = self.options.order == RowOrder::Canonical;
+        for (testament, books) in books_by_testament.iter() {
+            if !books.is_empty() {
+                f.write_all(format!("\n## {}\n", testament.name()).as_bytes())?;
+            }
+
+            for book in books {
+                let anchor = book_anchor(book);
+                f.write_all(format!("\n### {} {{#{}}}\n", book, anchor).as_bytes())?;
+
+                let refs = self.ordered(&posts.refs_by_book[book].0, posts);
+                self.write_refs_tables(
+                    &mut f,
+                    book,
+                    Some(&anchor),
+                    &refs,
+                    posts,
+                    with_chapter_headings,
+                )?;
+            }
+        }
+
+        f.flush()?;
+        Ok(())
+    }
+
     pub fn write_references(
         &mut self,
         posts: &AllReferences,
-        with_sequence_numbers: bool,
+        coverage: Option<&[BookCoverage]>,
     ) -> anyhow::Result<()> {
+        if let Some(single_page) = self.options.single_page.clone() {
+            return self.write_single_page(&single_page, posts, coverage);
+        }
+
         self.c.create_branch().and_then(|f| {
             for testament in Testament::all() {
                 let mut hrefs = Vec::new();
 
-                self.write_refs(
-                    testament.books_with_abbrev(),
-                    &mut hrefs,
-                    posts,
-                    with_sequence_numbers,
-                )?;
-                self.write_grid(&f, testament.name(), &hrefs)?;
+                self.write_refs(testament.books(), &mut hrefs, posts)?;
+                self.write_grid(&f, testament.name(), &hrefs, Self::BOOK_ROW_SIZE)?;
+            }
+            if let Some(coverage) = coverage {
+                self.write_coverage(&f, coverage)?;
             }
 
             Ok(())
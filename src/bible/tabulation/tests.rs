@@ -2,6 +2,8 @@
 
 use super::super::*;
 use super::*;
+use itertools::Itertools;
+use proptest::prelude::*;
 use test_case::test_case;
 
 fn unpack(refs: BookReferences) -> Vec<(usize, Vec<CInt>)> {
@@ -62,10 +64,13 @@ fn test_book_references_from_separated(
     }
 
     fn create_book_references_1(pcs: Vec<(usize, CInt, VInt)>) -> BookReferences1 {
-        let mut refs1 = BookReferences1::new(pcs[0].0, create_chapter_verses(pcs[0].1, pcs[0].2));
+        let mut refs1 = BookReferences1::new(PostReferences1::new(
+            pcs[0].0,
+            create_chapter_verses(pcs[0].1, pcs[0].2),
+        ));
 
         for pc in pcs.iter().skip(1) {
-            refs1.0.push(PostReferences1::new(
+            refs1.0.insert(PostReferences1::new(
                 pc.0,
                 create_chapter_verses(pc.1, pc.2),
             ));
@@ -81,3 +86,339 @@ fn test_book_references_from_separated(
         expected
     );
 }
+
+#[test]
+fn test_book_references_from_separated_keeps_parallels_apart() {
+    let cv = |c: CInt, v: VInt| ChapterVerses::new(Some(Chapter(c)), VSpans(vec![VSpan::Point(v)]));
+
+    let mut refs1 = BookReferences1::new(PostReferences1::new(1, cv(8, 23)));
+    refs1.0.insert(PostReferences1::parallel(1, cv(9, 1)));
+    refs1.0.insert(PostReferences1::new(1, cv(10, 1)));
+
+    let refs = BookReferences::from_separated(refs1);
+
+    assert_eq!(
+        refs.0
+            .iter()
+            .map(|r| (r.post_index, r.via_parallel, r.cvs.to_string()))
+            .collect::<Vec<(usize, bool, String)>>(),
+        vec![
+            (1, false, "8:23".to_string()),
+            (1, true, "9:1".to_string()),
+            (1, false, "10:1".to_string())
+        ]
+    );
+}
+
+#[test_case(Some(8), vec![VSpan::between(28, 39)], vec!["v8-28"]; "verses")]
+#[test_case(Some(8), vec![VSpan::at(1), VSpan::between(28, 39)], vec!["v8-1", "v8-28"]; "several spans")]
+#[test_case(Some(8), vec![], vec!["chapter-8"]; "whole chapter")]
+#[test_case(None, vec![VSpan::at(3)], vec!["v3"]; "single chapter book")]
+fn test_cv_anchors(chapter: Option<CInt>, vspans: Vec<VSpan>, expected: Vec<&str>) {
+    let cv = ChapterVerses::new(chapter.map(Chapter), VSpans::from_iter(vspans));
+
+    assert_eq!(cv_anchors(&cv), expected);
+}
+
+#[test_case("Romans", "v8-28", "romans-v8-28"; "simple")]
+#[test_case("1 Corinthians", "chapter-13", "1-corinthians-chapter-13"; "numbered book")]
+#[test_case("Song of Solomon", "v2-1", "song-of-solomon-v2-1"; "several words")]
+fn test_anchor_within_book(book: &str, anchor: &str, expected: &str) {
+    assert_eq!(
+        anchor_within(Some(&book_anchor(book)), anchor.to_string()),
+        expected
+    );
+}
+
+/// separated references as (post index, via parallel, chapter, verse), at most one per post and chapter
+fn separated_refs() -> impl Strategy<Value = Vec<(usize, bool, CInt, VInt)>> {
+    prop::collection::btree_map(
+        (0..4usize, prop::bool::weighted(0.2), 1..16 as CInt),
+        1..6 as VInt,
+        1..40,
+    )
+    .prop_map(|refs| {
+        refs.into_iter()
+            .map(|((post_index, via_parallel, c), v)| (post_index, via_parallel, c, v))
+            .collect()
+    })
+}
+
+fn book_references_1(refs: &[(usize, bool, CInt, VInt)]) -> BookReferences1 {
+    let cv = |c: CInt, v: VInt| ChapterVerses::new(Some(Chapter(c)), VSpans(vec![VSpan::Point(v)]));
+    let r1 = |&(post_index, via_parallel, c, v): &(usize, bool, CInt, VInt)| {
+        if via_parallel {
+            PostReferences1::parallel(post_index, cv(c, v))
+        } else {
+            PostReferences1::new(post_index, cv(c, v))
+        }
+    };
+
+    BookReferences1(refs.iter().map(r1).collect())
+}
+
+fn chapters(r: &PostReferences) -> Vec<CInt> {
+    r.cvs.0.iter().map(|cv| cv.chapter.unwrap().0).collect()
+}
+
+/// whether a post other than the given one cites a chapter strictly between lo and hi
+fn cited_by_other_between(
+    refs: &[(usize, bool, CInt, VInt)],
+    post: (usize, bool),
+    lo: CInt,
+    hi: CInt,
+) -> bool {
+    refs.iter()
+        .any(|&(p, via, c, _)| (p, via) != post && lo < c && c < hi)
+}
+
+/// fewest rows for the post, by considering every way of splitting its chapters
+fn fewest_rows(refs: &[(usize, bool, CInt, VInt)], post: (usize, bool)) -> usize {
+    let chapters = refs
+        .iter()
+        .filter(|&&(p, via, _, _)| (p, via) == post)
+        .map(|&(_, _, c, _)| c)
+        .sorted()
+        .collect::<Vec<CInt>>();
+
+    // fewest[j] is the fewest rows for the first j chapters
+    let mut fewest = vec![0];
+    for j in 1..=chapters.len() {
+        fewest.push(
+            (0..j)
+                .filter(|&i| !cited_by_other_between(refs, post, chapters[i], chapters[j - 1]))
+                .map(|i| fewest[i] + 1)
+                .min()
+                .unwrap(),
+        );
+    }
+
+    fewest[chapters.len()]
+}
+
+proptest! {
+    #[test]
+    fn prop_book_references_independent_of_order(
+        (refs, shuffled) in separated_refs().prop_flat_map(|refs| (Just(refs.clone()), Just(refs).prop_shuffle()))
+    ) {
+        prop_assert_eq!(
+            BookReferences::from_separated(book_references_1(&refs)).0,
+            BookReferences::from_separated(book_references_1(&shuffled)).0
+        );
+    }
+
+    #[test]
+    fn prop_book_references_keep_every_reference(refs in separated_refs()) {
+        let rows = BookReferences::from_separated(book_references_1(&refs)).0;
+
+        let mut laid_out = rows
+            .iter()
+            .flat_map(|r| {
+                r.cvs.0.iter().map(|cv| {
+                    (r.post_index, r.via_parallel, cv.chapter.unwrap().0, cv.verses.0[0].lower())
+                })
+            })
+            .collect::<Vec<(usize, bool, CInt, VInt)>>();
+        laid_out.sort();
+
+        prop_assert_eq!(laid_out, refs);
+    }
+
+    #[test]
+    fn prop_book_references_in_canonical_order(refs in separated_refs()) {
+        let rows = BookReferences::from_separated(book_references_1(&refs)).0;
+
+        for pair in rows.windows(2) {
+            prop_assert!(pair[0] < pair[1]);
+        }
+
+        for r in rows.iter() {
+            let chapters = chapters(r);
+            prop_assert!(chapters.windows(2).all(|c| c[0] < c[1]));
+            prop_assert!(!cited_by_other_between(
+                &refs,
+                (r.post_index, r.via_parallel),
+                chapters[0],
+                *chapters.last().unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn prop_book_references_fewest_rows(refs in separated_refs()) {
+        let rows = BookReferences::from_separated(book_references_1(&refs)).0;
+
+        for post in refs.iter().map(|&(p, via, _, _)| (p, via)).unique() {
+            prop_assert_eq!(
+                rows.iter().filter(|r| (r.post_index, r.via_parallel) == post).count(),
+                fewest_rows(&refs, post)
+            );
+        }
+    }
+}
+
+#[test_case(Some("2023-08-30T06:00:00Z"), Some("2023-08-30"); "date and time")]
+#[test_case(Some("2023-08-30"), Some("2023-08-30"); "date only")]
+#[test_case(Some("last Tuesday"), None; "not a date")]
+#[test_case(None, None; "undated")]
+fn test_post_date(date: Option<&str>, expected: Option<&str>) {
+    let mut header = Header::new("Post", "");
+    header.date = date.map(|date| date.to_string());
+
+    assert_eq!(
+        post_date(&header).map(|date| date.date().to_string()),
+        expected.map(|date| date.to_string())
+    );
+}
+
+/// refuses to create pages, since these tests write nothing
+struct NoCreate;
+
+impl NoCreate {
+    fn refuse<T>() -> anyhow::Result<T> {
+        Err(anyhow::anyhow!("no pages are written in these tests"))
+    }
+}
+
+impl Create for NoCreate {
+    fn create_branch(&mut self) -> anyhow::Result<std::fs::File> {
+        Self::refuse()
+    }
+
+    fn create_leaf(&mut self, _header: &Header) -> anyhow::Result<(std::fs::File, String)> {
+        Self::refuse()
+    }
+
+    fn create_section(&mut self, _header: &Header) -> anyhow::Result<(Box<dyn Create>, String)> {
+        Self::refuse()
+    }
+}
+
+fn writer(pericopes: &Pericopes, order: RowOrder, with_dates: bool) -> Writer<'_> {
+    Writer::new(
+        Box::new(NoCreate),
+        IndexOptions {
+            style: Style::plain().clone(),
+            pericopes,
+            excerpts: None,
+            with_sequence_numbers: false,
+            with_translations: false,
+            chapter_pages_over: None,
+            order,
+            with_dates,
+            single_page: None,
+            with_coverage: false,
+        },
+    )
+}
+
+/// posts citing Romans in canonical order, but not in order of date or title
+fn dated_posts() -> AllReferences {
+    let mut all = all_references(&[
+        (Some("2023-09-30T06:00:00Z"), "Romans 1:1"),
+        (None, "Romans 2:1"),
+        (Some("2023-08-30"), "Romans 3:1"),
+        (Some("2023-10-30T06:00:00Z"), "Romans 4:1"),
+    ]);
+    for (m, title) in all.metadata.iter_mut().zip(["b", "D", "c", "A"]) {
+        m.header.title = Some(title.to_string());
+    }
+    all.calculate_post_sequence_numbers();
+    all.coalesce();
+
+    all
+}
+
+#[test_case(RowOrder::Canonical, vec![0, 1, 2, 3]; "canonical")]
+#[test_case(RowOrder::Date, vec![2, 0, 3, 1]; "date with undated last")]
+#[test_case(RowOrder::Sequence, vec![2, 0, 3, 1]; "sequence with unnumbered last")]
+#[test_case(RowOrder::Title, vec![3, 0, 2, 1]; "title ignoring case")]
+fn test_ordered(order: RowOrder, expected: Vec<usize>) {
+    let pericopes = Pericopes::default();
+    let posts = dated_posts();
+
+    let ordered = writer(&pericopes, order, false).ordered(&posts.refs_by_book["Romans"].0, &posts);
+
+    assert_eq!(
+        ordered.iter().map(|r| r.post_index).collect::<Vec<usize>>(),
+        expected
+    );
+}
+
+#[test_case(false, vec![2, 2, 2, 2]; "without dates")]
+#[test_case(true, vec![3, 3, 3, 3]; "with dates")]
+fn test_rows_with_dates(with_dates: bool, expected_widths: Vec<usize>) {
+    let pericopes = Pericopes::default();
+    let posts = dated_posts();
+    let w = writer(&pericopes, RowOrder::Canonical, with_dates);
+    let refs = w.ordered(&posts.refs_by_book["Romans"].0, &posts);
+
+    let rows = w
+        .rows("Romans", None, &refs, &posts, HashSet::new())
+        .unwrap();
+
+    assert_eq!(
+        rows.iter().map(|row| row.len()).collect::<Vec<usize>>(),
+        expected_widths
+    );
+    if with_dates {
+        assert_eq!(
+            rows.iter()
+                .map(|row| row[2].as_str())
+                .collect::<Vec<&str>>(),
+            vec!["2023-09-30", "", "2023-08-30", "2023-10-30"]
+        );
+    }
+}
+
+/// a post in the ESV quoting one verse from the WEB, and a post with no translation
+fn translated_posts() -> AllReferences {
+    let mut all = all_references(&[
+        (None, "Romans 8:28-30 and Romans 12:1"),
+        (None, "Romans 8:28"),
+    ]);
+    all.metadata[0].header.translation = Some("ESV".to_string());
+    all.quoted_translations_by_index[0] = quoted_translations(
+        r#"{{< quote source="Romans 8:29" translation="WEB" >}}{{< /quote >}}"#,
+    );
+
+    all
+}
+
+#[test_case(0, "Romans 12:1", vec!["ESV"]; "post translation")]
+#[test_case(0, "Romans 8:29", vec!["WEB"]; "quote overriding post translation")]
+#[test_case(0, "Romans 8:28-30", vec!["WEB"]; "quote within span")]
+#[test_case(0, "Romans 8:29 and Romans 12:1", vec!["WEB", "ESV"]; "quoted and unquoted chapters")]
+#[test_case(0, "Romans 8:29, 31", vec!["WEB", "ESV"]; "quoted and unquoted spans")]
+#[test_case(1, "Romans 8:28", vec![]; "no translation")]
+fn test_translations(post_index: usize, src: &str, expected: Vec<&str>) {
+    let posts = translated_posts();
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+
+    assert_eq!(posts.translations(post_index, book, &cvs.0), expected);
+}
+
+#[test]
+fn test_rows_with_translations() {
+    let pericopes = Pericopes::default();
+    let mut posts = translated_posts();
+    posts.coalesce();
+    let mut w = writer(&pericopes, RowOrder::Canonical, false);
+    w.options.with_translations = true;
+    let refs = w.ordered(&posts.refs_by_book["Romans"].0, &posts);
+
+    let rows = w
+        .rows("Romans", None, &refs, &posts, HashSet::new())
+        .unwrap();
+
+    assert_eq!(
+        rows.iter()
+            .map(|row| row[1].as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            r#"[Post 1]({{<relref "/post/1.md" >}})"#,
+            r#"[Post 0]({{<relref "/post/0.md" >}})<span style="font-size:smaller; padding-left:0.5em;">(WEB, ESV)</span>"#,
+        ]
+    );
+}
@@ -68,62 +68,3 @@ fn test_chapter_verses_cmp(cv1: (CInt, Vec<VInt>), cv2: (CInt, Vec<VInt>), expec
         &expected
     );
 }
-
-#[test_case(
-    vec![(1, vec![10, 11]), (2, vec![10]), (3, vec![4])],
-    vec![(1, vec![10, 11]), (2, vec![10]), (3, vec![4])],
-    Some(Equal);
-    "same chapters and verses")]
-#[test_case(
-    vec![(1, vec![9, 11]), (2, vec![10]), (3, vec![4])],
-    vec![(1, vec![10, 11]), (2, vec![10]), (3, vec![4])],
-    Some(Less);
-    "same chapters less verses 1")]
-#[test_case(
-    vec![(1, vec![10, 11]), (2, vec![10]), (3, vec![3])],
-    vec![(1, vec![10, 11]), (2, vec![10]), (3, vec![4])],
-    Some(Less);
-    "same chapters less verses 2")]
-#[test_case(
-    vec![(1, vec![10, 11]), (2, vec![10])],
-    vec![(3, vec![1])],
-    Some(Less);
-    "all less")]
-#[test_case(
-    vec![(1, vec![1]), (3, vec![1])],
-    vec![(2, vec![1])],
-    None;
-    "in-between chapters 1")]
-#[test_case(
-    vec![(1, vec![1]), (2, vec![1]), (3, vec![1])],
-    vec![(2, vec![1])],
-    None;
-    "in-between chapters 2")]
-#[test_case(
-    vec![(1, vec![1]), (2, vec![2])],
-    vec![(2, vec![1])],
-    Some(Less);
-    "in-between verses")]
-#[test_case(
-    vec![(1, vec![1]), (3, vec![1])],
-    vec![(2, vec![1]), (4, vec![1])],
-    None;
-    "interleaved chapters")]
-fn test_chapters_verses_partial_cmp(
-    cvs1: Vec<(CInt, Vec<VInt>)>,
-    cvs2: Vec<(CInt, Vec<VInt>)>,
-    expected: Option<Ordering>,
-) {
-    fn create_chapters_verses(cvs: Vec<(CInt, Vec<VInt>)>) -> ChaptersVerses {
-        ChaptersVerses(
-            cvs.into_iter()
-                .map(|(c, vs)| create_chapter_verses(c, vs))
-                .collect::<Vec<ChapterVerses>>(),
-        )
-    }
-
-    assert_eq!(
-        &create_chapters_verses(cvs1).partial_cmp(&create_chapters_verses(cvs2)),
-        &expected
-    );
-}
@@ -0,0 +1,329 @@
+use super::books::all_books;
+use super::osis::{osis_book, osis_point};
+use super::*;
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// the text of a single verse
+#[derive(PartialEq, Eq, Debug)]
+struct Verse {
+    book: &'static str,
+    chapter: CInt,
+    verse: VInt,
+    text: String,
+}
+
+/// formats from which a Bible text may be imported
+#[derive(clap::ValueEnum, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TextFormat {
+    Osis,
+    Usfm,
+    Zefania,
+    Csv,
+}
+
+/// A Bible text imported into a directory, as one verse per line in canonical order,
+/// with an index of where each chapter starts, so that lookup reads only the chapters it needs.
+#[derive(Debug)]
+pub struct TextStore {
+    verses_path: PathBuf,
+    // offset and length of each chapter in the verses file
+    index: HashMap<(&'static str, CInt), (u64, u64)>,
+}
+
+impl TextStore {
+    const VERSES_FILE: &'static str = "verses.txt";
+    const INDEX_FILE: &'static str = "index.txt";
+
+    /// Import a translation into the store directory, replacing whatever was there.
+    /// USFM and CSV may be given as a directory of files, as is usual for USFM.
+    pub fn import<P, Q>(format: TextFormat, input: P, store: Q) -> Result<TextStore>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let mut verses = Vec::new();
+
+        for path in input_files(input.as_ref())? {
+            let text =
+                fs::read_to_string(&path).context(format!("read(\"{}\")", path.display()))?;
+
+            let file_verses = match format {
+                TextFormat::Osis => import::from_osis(&text),
+                TextFormat::Usfm => import::from_usfm(&text),
+                TextFormat::Zefania => import::from_zefania(&text),
+                TextFormat::Csv => import::from_csv(&text),
+            }
+            .context(format!("parse(\"{}\")", path.display()))?;
+
+            verses.extend(file_verses);
+        }
+
+        if verses.is_empty() {
+            return Err(anyhow!("no verses found in {}", input.as_ref().display()));
+        }
+
+        TextStore::write(verses, store.as_ref())?;
+        TextStore::open(store)
+    }
+
+    fn write(mut verses: Vec<Verse>, store: &Path) -> Result<()> {
+        let book_order = all_books()
+            .enumerate()
+            .map(|(i, book)| (book, i))
+            .collect::<HashMap<&str, usize>>();
+        verses.sort_by_key(|v| (book_order[v.book], v.chapter, v.verse));
+        verses.dedup_by_key(|v| (v.book, v.chapter, v.verse));
+
+        fs::create_dir_all(store)?;
+        let mut verses_file = BufWriter::new(File::create(store.join(Self::VERSES_FILE))?);
+        let mut index_file = BufWriter::new(File::create(store.join(Self::INDEX_FILE))?);
+
+        let mut offset = 0_u64;
+        for (i, v) in verses.iter().enumerate() {
+            let start_of_chapter =
+                i == 0 || verses[i - 1].book != v.book || verses[i - 1].chapter != v.chapter;
+            if start_of_chapter {
+                let chapter_len = verses[i..]
+                    .iter()
+                    .take_while(|v1| v1.book == v.book && v1.chapter == v.chapter)
+                    .map(|v1| verse_line(v1).len() as u64)
+                    .sum::<u64>();
+                writeln!(
+                    index_file,
+                    "{}.{}\t{}\t{}",
+                    osis_book(v.book),
+                    v.chapter,
+                    offset,
+                    chapter_len
+                )?;
+            }
+
+            let line = verse_line(v);
+            verses_file.write_all(line.as_bytes())?;
+            offset += line.len() as u64;
+        }
+
+        verses_file.flush()?;
+        index_file.flush()?;
+        Ok(())
+    }
+
+    /// open a store previously created by `import`
+    pub fn open<P>(store: P) -> Result<TextStore>
+    where
+        P: AsRef<Path>,
+    {
+        let index_path = store.as_ref().join(Self::INDEX_FILE);
+        let index_text = fs::read_to_string(&index_path)
+            .context(format!("read(\"{}\")", index_path.display()))?;
+
+        let mut index = HashMap::new();
+        for line in index_text.lines() {
+            let bad_index = || anyhow!("bad index line \"{}\" in {}", line, index_path.display());
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            match fields[..] {
+                [chapter, offset, len] => match osis_point(chapter) {
+                    Ok((book, Some(chapter), None)) => {
+                        index.insert(
+                            (book, chapter),
+                            (
+                                offset.parse::<u64>().map_err(|_| bad_index())?,
+                                len.parse::<u64>().map_err(|_| bad_index())?,
+                            ),
+                        );
+                    }
+                    _ => return Err(bad_index()),
+                },
+                _ => return Err(bad_index()),
+            }
+        }
+
+        Ok(TextStore {
+            verses_path: store.as_ref().join(Self::VERSES_FILE),
+            index,
+        })
+    }
+
+    /// each verse with its text, for a whole chapter if no verses are given,
+    /// and empty if the store doesn't have the chapter
+    pub fn verses(&self, book: &'static str, cv: &ChapterVerses) -> Result<Vec<(VInt, String)>> {
+        let chapter = cv.chapter.unwrap_or(Chapter(1)).0;
+        let (offset, len) = match self.index.get(&(book, chapter)) {
+            Some(location) => *location,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut f = File::open(&self.verses_path)
+            .context(format!("open(\"{}\")", self.verses_path.display()))?;
+        f.seek(SeekFrom::Start(offset))?;
+
+        let mut verses = Vec::new();
+        for line in BufReader::new(f.take(len)).lines() {
+            let line = line?;
+            let (id, text) = line
+                .split_once('\t')
+                .ok_or(anyhow!("bad verse line \"{}\"", line))?;
+            let verse = match osis_point(id)? {
+                (_, _, Some(verse)) => verse,
+                _ => return Err(anyhow!("bad verse line \"{}\"", line)),
+            };
+
+            if cv.verses.is_empty()
+                || cv
+                    .verses
+                    .into_iter()
+                    .any(|vspan| vspan.lower() <= verse && verse <= vspan.upper())
+            {
+                verses.push((verse, text.to_string()));
+            }
+        }
+
+        Ok(verses)
+    }
+
+    /// the text of the verses, joined into a single passage
+    pub fn text(&self, book: &'static str, cv: &ChapterVerses) -> Result<String> {
+        Ok(self
+            .verses(book, cv)?
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect::<Vec<String>>()
+            .join(" "))
+    }
+}
+
+/// A store imported from CSV into a temporary directory, for tests.
+/// The directory, with the store in `store` inside it, is removed when dropped.
+#[cfg(test)]
+pub fn temp_store(csv: &str) -> (tempfile::TempDir, TextStore) {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.csv");
+    fs::write(&input, csv).unwrap();
+    let store = TextStore::import(TextFormat::Csv, &input, dir.path().join("store")).unwrap();
+
+    (dir, store)
+}
+
+/// text stores for the translations we have, and for when no translation is named
+#[derive(Default, Debug)]
+pub struct Texts {
+    default: Option<TextStore>,
+    // by lowercase translation name
+    by_translation: HashMap<String, TextStore>,
+}
+
+impl Texts {
+    pub fn new(default: Option<TextStore>) -> Self {
+        Texts {
+            default,
+            by_translation: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, translation: &str, store: TextStore) {
+        self.by_translation
+            .insert(translation.to_lowercase(), store);
+    }
+
+    /// the store for the named translation, or the default if none is named
+    pub fn get(&self, translation: Option<&str>) -> Option<&TextStore> {
+        match translation {
+            Some(translation) => self.by_translation.get(&translation.to_lowercase()),
+            None => self.default.as_ref(),
+        }
+    }
+}
+
+/// excerpts of verse text from a store, cut short at a given length
+#[derive(Debug)]
+pub struct Excerpts {
+    store: TextStore,
+    length: usize,
+}
+
+impl Excerpts {
+    pub const DEFAULT_LENGTH: usize = 100;
+
+    pub fn new(store: TextStore, length: usize) -> Self {
+        Excerpts { store, length }
+    }
+
+    /// the start of the text of all the chapters and verses, cut short at a word boundary
+    pub fn excerpt(&self, book: &'static str, cvs: &ChaptersVerses) -> Result<String> {
+        let mut text = String::new();
+        for cv in cvs {
+            if text.chars().count() > self.length {
+                break;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&self.store.text(book, cv)?);
+        }
+
+        Ok(truncated(&text, self.length))
+    }
+}
+
+/// the text cut short at a word boundary to at most `length` characters including the ellipsis, if it was cut
+fn truncated(text: &str, length: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    // leaving room for the ellipsis
+    let length = length.saturating_sub(1);
+    let mut result = String::new();
+    for word in text.split_whitespace() {
+        let separator_len = if result.is_empty() { 0 } else { 1 };
+        if result.chars().count() + separator_len + word.chars().count() > length {
+            break;
+        }
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push_str(word);
+    }
+
+    // a single word longer than the length is cut wherever it must be
+    if result.is_empty() {
+        result = text.chars().take(length).collect();
+    }
+
+    result.trim_end_matches([',', ';', ':', '.']).to_string() + "…"
+}
+
+/// the line for a verse in the verses file, with whitespace collapsed so it remains a single line
+fn verse_line(v: &Verse) -> String {
+    format!(
+        "{}.{}.{}\t{}\n",
+        osis_book(v.book),
+        v.chapter,
+        v.verse,
+        v.text.split_whitespace().collect::<Vec<&str>>().join(" ")
+    )
+}
+
+/// the input file, or all the files in the input directory in order of name
+fn input_files(input: &Path) -> Result<Vec<PathBuf>> {
+    if input.is_dir() {
+        let mut paths = fs::read_dir(input)
+            .context(format!("read_dir(\"{}\")", input.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, _>>()?;
+        paths.retain(|path| path.is_file());
+        paths.sort();
+        Ok(paths)
+    } else {
+        Ok(vec![input.to_path_buf()])
+    }
+}
+
+mod import;
+mod tests;
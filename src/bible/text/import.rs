@@ -0,0 +1,298 @@
+use super::super::books::{all_books, book};
+use super::super::osis::{book_from_osis, osis_point};
+use super::*;
+use lazy_static::lazy_static;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+
+/// book IDs used by USFM, in canonical order
+const USFM_BOOKS: [&str; 66] = [
+    "GEN", "EXO", "LEV", "NUM", "DEU", "JOS", "JDG", "RUT", "1SA", "2SA", "1KI", "2KI", "1CH",
+    "2CH", "EZR", "NEH", "EST", "JOB", "PSA", "PRO", "ECC", "SNG", "ISA", "JER", "LAM", "EZK",
+    "DAN", "HOS", "JOL", "AMO", "OBA", "JON", "MIC", "NAM", "HAB", "ZEP", "HAG", "ZEC", "MAL",
+    "MAT", "MRK", "LUK", "JHN", "ACT", "ROM", "1CO", "2CO", "GAL", "EPH", "PHP", "COL", "1TH",
+    "2TH", "1TI", "2TI", "TIT", "PHM", "HEB", "JAS", "1PE", "2PE", "1JN", "2JN", "3JN", "JUD",
+    "REV",
+];
+
+/// the book by its number in canonical order, starting from 1
+fn book_by_number(n: usize) -> Option<&'static str> {
+    n.checked_sub(1).and_then(|i| all_books().nth(i))
+}
+
+/// the verse being collected
+struct Collector {
+    verses: Vec<Verse>,
+    current: Option<Verse>,
+}
+
+impl Collector {
+    fn new() -> Self {
+        Collector {
+            verses: Vec::new(),
+            current: None,
+        }
+    }
+
+    fn start(&mut self, book: &'static str, chapter: CInt, verse: VInt) {
+        self.finish();
+        self.current = Some(Verse {
+            book,
+            chapter,
+            verse,
+            text: String::new(),
+        });
+    }
+
+    fn push_str(&mut self, text: &str) {
+        if let Some(v) = self.current.as_mut() {
+            v.text.push_str(text);
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(v) = self.current.take() {
+            if !v.text.trim().is_empty() {
+                self.verses.push(v);
+            }
+        }
+    }
+
+    fn into_verses(mut self) -> Vec<Verse> {
+        self.finish();
+        self.verses
+    }
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Result<Option<String>> {
+    Ok(match e.try_get_attribute(name)? {
+        Some(a) => Some(a.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// the book, chapter and verse of an OSIS ID, ignoring any work prefix and all but the first of several IDs
+fn osis_verse(osis_id: &str) -> Result<(&'static str, CInt, VInt)> {
+    let first = osis_id.split_whitespace().next().unwrap_or("");
+    let id = first.rsplit_once(':').map_or(first, |(_work, id)| id);
+
+    match osis_point(id)? {
+        (book, Some(chapter), Some(verse)) => Ok((book, chapter, verse)),
+        _ => Err(anyhow!("expected OSIS verse, found {}", osis_id)),
+    }
+}
+
+/// Verses from OSIS XML, with verses either as containers or as milestones.
+/// Notes and titles are not part of the text.
+pub fn from_osis(text: &str) -> Result<Vec<Verse>> {
+    let mut reader = Reader::from_str(text);
+    let mut collector = Collector::new();
+    let mut skip_depth = 0;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"verse" => {
+                    if let Some(osis_id) = attribute(&e, "osisID")? {
+                        let (book, chapter, verse) = osis_verse(&osis_id)?;
+                        collector.start(book, chapter, verse);
+                    }
+                }
+                b"note" | b"title" => skip_depth += 1,
+                _ => (),
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"verse" => {
+                if attribute(&e, "eID")?.is_some() {
+                    collector.finish();
+                } else if let Some(osis_id) = attribute(&e, "osisID")? {
+                    let (book, chapter, verse) = osis_verse(&osis_id)?;
+                    collector.start(book, chapter, verse);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"verse" => collector.finish(),
+                b"note" | b"title" => skip_depth -= 1,
+                _ => (),
+            },
+            Event::Text(e) if skip_depth == 0 => collector.push_str(&e.unescape()?),
+            Event::CData(e) if skip_depth == 0 => {
+                collector.push_str(&String::from_utf8_lossy(&e.into_inner()))
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(collector.into_verses())
+}
+
+/// Verses from Zefania XML.  Notes are not part of the text.
+pub fn from_zefania(text: &str) -> Result<Vec<Verse>> {
+    let mut reader = Reader::from_str(text);
+    let mut collector = Collector::new();
+    let mut book = None;
+    let mut chapter = None;
+    let mut skip_depth = 0;
+
+    let number = |e: &BytesStart, name: &str| -> Result<usize> {
+        let value = attribute(e, name)?.ok_or(anyhow!("missing {}", name))?;
+        value
+            .trim()
+            .parse::<usize>()
+            .context(format!("bad {} {}", name, value))
+    };
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"BIBLEBOOK" => {
+                    let n = number(&e, "bnumber")?;
+                    book = Some(book_by_number(n).ok_or(anyhow!("unknown book number {}", n))?);
+                }
+                b"CHAPTER" => chapter = Some(CInt::try_from(number(&e, "cnumber")?)?),
+                b"VERS" => {
+                    let verse = VInt::try_from(number(&e, "vnumber")?)?;
+                    match (book, chapter) {
+                        (Some(book), Some(chapter)) => collector.start(book, chapter, verse),
+                        _ => return Err(anyhow!("verse outside book and chapter")),
+                    }
+                }
+                b"NOTE" => skip_depth += 1,
+                _ => (),
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"VERS" => collector.finish(),
+                b"NOTE" => skip_depth -= 1,
+                _ => (),
+            },
+            Event::Text(e) if skip_depth == 0 => collector.push_str(&e.unescape()?),
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(collector.into_verses())
+}
+
+/// Verses from USFM.  Headings, introductions, footnotes and cross references are not part of the text.
+pub fn from_usfm(text: &str) -> Result<Vec<Verse>> {
+    lazy_static! {
+        static ref NOTE_RE: Regex = Regex::new(r"(?s)\\(f|fe|x|ef|ex)\s.*?\\(f|fe|x|ef|ex)\*").unwrap();
+        // word attributes, as in \w gracious|strong="H2603"\w*
+        static ref ATTRIBUTES_RE: Regex = Regex::new(r"\|[^\\]*").unwrap();
+        static ref LEADING_MARKER_RE: Regex = Regex::new(r"^\\([a-z0-9]+)\s*").unwrap();
+        static ref VERSE_RE: Regex = Regex::new(r"\\v\s+(\d+)\S*\s*").unwrap();
+        static ref MARKER_RE: Regex = Regex::new(r"\\\+?[a-z0-9]+\*?").unwrap();
+    }
+
+    fn is_heading(marker: &str) -> bool {
+        ["h", "d", "r", "cl", "cp", "rem", "ide", "sts", "usfm"].contains(&marker)
+            || ["toc", "mt", "ms", "mr", "s", "i"]
+                .iter()
+                .any(|prefix| marker.starts_with(prefix))
+    }
+
+    let text = NOTE_RE.replace_all(text, "");
+    let text = ATTRIBUTES_RE.replace_all(&text, "");
+
+    let mut collector = Collector::new();
+    let mut book = None;
+    let mut chapter = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (marker, rest) = match LEADING_MARKER_RE.captures(line) {
+            Some(cap) => (
+                cap.get(1).unwrap().as_str(),
+                &line[cap.get(0).unwrap().end()..],
+            ),
+            None => ("", line),
+        };
+
+        match marker {
+            "id" => {
+                collector.finish();
+                let id = rest.split_whitespace().next().unwrap_or("");
+                let i = USFM_BOOKS
+                    .iter()
+                    .position(|b| *b == id)
+                    .ok_or(anyhow!("unknown USFM book {}", id))?;
+                book = book_by_number(i + 1);
+                chapter = None;
+            }
+            "c" => {
+                collector.finish();
+                let c = rest.split_whitespace().next().unwrap_or("");
+                chapter = Some(c.parse::<CInt>().context(format!("bad chapter {}", c))?);
+            }
+            marker if is_heading(marker) => (),
+            // paragraph markers and verses, where the marker is simply stripped along with any others
+            _ => {
+                let mut done = 0;
+                for cap in VERSE_RE.captures_iter(line) {
+                    let m = cap.get(0).unwrap();
+                    collector.push_str(&MARKER_RE.replace_all(&line[done..m.start()], ""));
+                    collector.push_str(" ");
+
+                    let v = cap.get(1).unwrap().as_str();
+                    let verse = v.parse::<VInt>().context(format!("bad verse {}", v))?;
+                    match (book, chapter) {
+                        (Some(book), Some(chapter)) => collector.start(book, chapter, verse),
+                        _ => return Err(anyhow!("verse {} outside book and chapter", v)),
+                    }
+                    done = m.end();
+                }
+                collector.push_str(&MARKER_RE.replace_all(&line[done..], ""));
+                collector.push_str(" ");
+            }
+        }
+    }
+
+    Ok(collector.into_verses())
+}
+
+/// Verses from CSV with a header row and columns for book, chapter, verse and text.
+/// The book may be a name, an OSIS ID, or its number in canonical order.
+pub fn from_csv(text: &str) -> Result<Vec<Verse>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(false)
+        .from_reader(text.as_bytes());
+    let mut verses = Vec::new();
+
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        // allowing for the header
+        let line = i + 2;
+
+        if record.len() != 4 {
+            return Err(anyhow!(
+                "line {}: expected book, chapter, verse and text",
+                line
+            ));
+        }
+
+        let b = record[0].trim();
+        let book = book_from_osis(b)
+            .or_else(|| book(Some(""), Some(b)))
+            .or_else(|| b.parse::<usize>().ok().and_then(book_by_number))
+            .ok_or(anyhow!("line {}: unknown book {}", line, b))?;
+        let chapter = record[1]
+            .trim()
+            .parse::<CInt>()
+            .context(format!("line {}: bad chapter {}", line, &record[1]))?;
+        let verse = record[2]
+            .trim()
+            .parse::<VInt>()
+            .context(format!("line {}: bad verse {}", line, &record[2]))?;
+
+        verses.push(Verse {
+            book,
+            chapter,
+            verse,
+            text: record[3].trim().to_string(),
+        });
+    }
+
+    Ok(verses)
+}
@@ -0,0 +1,151 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+fn unpack(verses: Vec<Verse>) -> Vec<String> {
+    verses
+        .into_iter()
+        .map(|v| {
+            format!(
+                "{} {}:{} {}",
+                v.book,
+                v.chapter,
+                v.verse,
+                v.text.split_whitespace().collect::<Vec<&str>>().join(" ")
+            )
+        })
+        .collect()
+}
+
+const OSIS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+<osisText osisIDWork="WEB">
+<div type="book" osisID="John">
+<chapter osisID="John.11">
+<title>The death of Lazarus</title>
+<verse osisID="John.11.35">Jesus wept.<note>The shortest verse.</note></verse>
+<p><verse sID="John.11.36" osisID="John.11.36"/>The Jews therefore said, &#8220;See how much he loved him!&#8221;<verse eID="John.11.36"/></p>
+</chapter>
+</div>
+</osisText>
+</osis>
+"#;
+
+#[test]
+fn test_from_osis() {
+    assert_eq!(
+        unpack(import::from_osis(OSIS).unwrap()),
+        vec![
+            "John 11:35 Jesus wept.",
+            "John 11:36 The Jews therefore said, “See how much he loved him!”"
+        ]
+    );
+}
+
+const ZEFANIA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<XMLBIBLE biblename="KJV">
+<BIBLEBOOK bnumber="19" bname="Psalms">
+<CHAPTER cnumber="23">
+<VERS vnumber="1">The LORD is my shepherd; I shall not want.</VERS>
+<VERS vnumber="2">He maketh me to lie down in green pastures<NOTE>or, pastures of tender grass</NOTE>: he leadeth me beside the still waters.</VERS>
+</CHAPTER>
+</BIBLEBOOK>
+</XMLBIBLE>
+"#;
+
+#[test]
+fn test_from_zefania() {
+    assert_eq!(
+        unpack(import::from_zefania(ZEFANIA).unwrap()),
+        vec![
+            "Psalms 23:1 The LORD is my shepherd; I shall not want.",
+            "Psalms 23:2 He maketh me to lie down in green pastures: he leadeth me beside the still waters."
+        ]
+    );
+}
+
+const USFM: &str = r#"\id ROM World English Bible
+\h Romans
+\toc1 Paul’s Letter to the Romans
+\mt1 Paul’s Letter to the Romans
+\c 8
+\s1 More than conquerors
+\p
+\v 38 For I am persuaded that neither death, nor life,\f + \fr 8:38 \ft Or, powers\f* nor angels,
+\q1 nor things present,
+\v 39 nor height, nor depth, nor any other \w created|strong="G2937"\w* thing will be able to separate us.
+"#;
+
+#[test]
+fn test_from_usfm() {
+    assert_eq!(
+        unpack(import::from_usfm(USFM).unwrap()),
+        vec![
+            "Romans 8:38 For I am persuaded that neither death, nor life, nor angels, nor things present,",
+            "Romans 8:39 nor height, nor depth, nor any other created thing will be able to separate us."
+        ]
+    );
+}
+
+const CSV: &str = r#"book,chapter,verse,text
+Gen,1,1,"In the beginning God created the heavens and the earth."
+1 John,4,8,"He who doesn't love doesn't know God, for God is love."
+65,1,3,"Beloved, while I was very eager to write to you..."
+"#;
+
+#[test]
+fn test_from_csv() {
+    assert_eq!(
+        unpack(import::from_csv(CSV).unwrap()),
+        vec![
+            "Genesis 1:1 In the beginning God created the heavens and the earth.",
+            "1 John 4:8 He who doesn't love doesn't know God, for God is love.",
+            "Jude 1:3 Beloved, while I was very eager to write to you..."
+        ]
+    );
+}
+
+#[test_case("book,chapter,verse,text\nXyz,1,1,x", "line 2: unknown book Xyz"; "unknown book")]
+#[test_case("book,chapter,verse,text\nGen,one,1,x", "line 2: bad chapter one"; "bad chapter")]
+#[test_case("book,chapter,verse\nGen,1,1", "line 2: expected book, chapter, verse and text"; "missing text")]
+fn test_from_csv_error(src: &str, expected: &str) {
+    assert_eq!(import::from_csv(src).unwrap_err().to_string(), expected);
+}
+
+#[test_case("Romans 8:38-39", vec![38, 39]; "verses")]
+#[test_case("Romans 8:39, 1", vec![1, 39]; "several spans")]
+#[test_case("Romans 8", vec![1, 38, 39]; "whole chapter")]
+#[test_case("Romans 9:1", vec![]; "missing chapter")]
+#[test_case("Jude v3", vec![3]; "single chapter book")]
+fn test_text_store(src: &str, expected: Vec<VInt>) {
+    let csv = "book,chapter,verse,text
+Rom,8,39,\"nor height, nor depth\"
+Rom,8,1,\"There is therefore now no condemnation\"
+Rom,8,38,\"For I am persuaded\"
+Jude,1,3,\"Beloved\"
+";
+    let (_dir, store) = temp_store(csv);
+    let (refs, _) = references("", src);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+    let cv = cvs.into_iter().next().unwrap();
+
+    let result = store
+        .verses(book, &cv)
+        .unwrap()
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect::<Vec<VInt>>();
+
+    assert_eq!(result, expected);
+}
+
+#[test_case("Jesus wept.", 20, "Jesus wept."; "short enough")]
+#[test_case("For God so loved the world, that he gave", 30, "For God so loved the world…"; "word boundary")]
+#[test_case("Mahershalalhashbaz", 10, "Mahershal…"; "single long word")]
+#[test_case("  Jesus wept.  ", 11, "Jesus wept."; "trimmed")]
+fn test_truncated(text: &str, length: usize, expected: &str) {
+    assert_eq!(truncated(text, length), expected);
+    assert!(truncated(text, length).chars().count() <= length);
+}
@@ -0,0 +1,144 @@
+use super::extraction::references;
+use super::index_links::{quotes, QUOTE_END_RE};
+use super::text::Texts;
+use super::*;
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// a quote which doesn't seem to come from the passage it cites
+#[derive(PartialEq, Eq, Debug)]
+pub struct Mismatch {
+    pub line: usize,
+    pub source: String,
+    pub problem: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "{}: quote from \"{}\" {}",
+            self.line, self.source, self.problem
+        )
+    }
+}
+
+/// Return the quotes whose text matches less than `threshold` percent of its words
+/// to the cited verses, in the order they appear in the text.
+///
+/// Words are compared ignoring case and punctuation, and must appear in the same order as in the verses,
+/// though the quote may skip verses or words, as with an ellipsis.
+pub fn mismatched_quotes(
+    raw_header: &str,
+    text: &str,
+    texts: &Texts,
+    threshold: u8,
+) -> Result<Vec<Mismatch>> {
+    let header_lines = raw_header.matches('\n').count();
+    let mut mismatches = Vec::new();
+
+    for (span, quote) in quotes(text) {
+        let source = match quote.source() {
+            Some(source) => source,
+            None => continue,
+        };
+        let quoted = match QUOTE_END_RE.find_at(text, span.end) {
+            Some(end) => &text[span.end..end.start()],
+            None => continue,
+        };
+
+        let mismatch = |problem: String| Mismatch {
+            line: header_lines + text[..span.start].matches('\n').count() + 1,
+            source: source.to_string(),
+            problem,
+        };
+
+        let store = match texts.get(quote.translation()) {
+            Some(store) => store,
+            None => {
+                mismatches.push(mismatch(match quote.translation() {
+                    Some(translation) => format!("has no text for translation {}", translation),
+                    None => "has no text, since no default is configured".to_string(),
+                }));
+                continue;
+            }
+        };
+
+        let (refs, _) = references("", source);
+        if refs.is_empty() {
+            mismatches.push(mismatch("has no reference".to_string()));
+            continue;
+        }
+
+        // canonical book order, so the match doesn't vary from run to run
+        let mut cited = Vec::new();
+        for (book, cvs) in refs.iter_canonical() {
+            for cv in cvs {
+                cited.push(store.text(book, cv)?);
+            }
+        }
+        let cited = cited.join(" ");
+
+        if cited.is_empty() {
+            mismatches.push(mismatch("has no text for the cited verses".to_string()));
+            continue;
+        }
+
+        let quoted_words = words(quoted);
+        if quoted_words.is_empty() {
+            continue;
+        }
+
+        let percent =
+            common_subsequence_len(&quoted_words, &words(&cited)) * 100 / quoted_words.len();
+        if percent < threshold as usize {
+            mismatches.push(mismatch(format!(
+                "matches only {}% of its words to the cited verses",
+                percent
+            )));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// the words of the text, lowercase and without punctuation, and ignoring any markup
+fn words(text: &str) -> Vec<String> {
+    lazy_static! {
+        static ref MARKUP_RE: Regex = Regex::new(r"<[^>]*>|\{\{[^}]*}}").unwrap();
+    }
+
+    MARKUP_RE
+        .replace_all(text, " ")
+        .split(|c: char| c.is_whitespace() || c == '—' || c == '–' || c == '-')
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(|c| c.to_lowercase())
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// length of the longest common subsequence
+fn common_subsequence_len(a: &[String], b: &[String]) -> usize {
+    let mut previous = vec![0; b.len() + 1];
+    let mut current = vec![0; b.len() + 1];
+
+    for a_word in a {
+        for (j, b_word) in b.iter().enumerate() {
+            current[j + 1] = if a_word == b_word {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+mod tests;
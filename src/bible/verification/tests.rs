@@ -0,0 +1,55 @@
+#![cfg(test)]
+
+use super::super::text::{temp_store, TextStore};
+use super::*;
+use tempfile::TempDir;
+use test_case::test_case;
+
+fn texts() -> (TempDir, Texts) {
+    let csv = r#"book,chapter,verse,text
+Rom,8,38,"For I am persuaded that neither death, nor life, nor angels, nor principalities, nor things present, nor things to come, nor powers,"
+Rom,8,39,"nor height, nor depth, nor any other created thing will be able to separate us from God's love which is in Christ Jesus our Lord."
+John,11,35,"Jesus wept."
+"#;
+    let (dir, store) = temp_store(csv);
+    let mut texts = Texts::new(Some(TextStore::open(dir.path().join("store")).unwrap()));
+    texts.insert("WEB", store);
+    (dir, texts)
+}
+
+const HEADER: &str = "---\ntitle: \"Conquerors\"\n---\n";
+
+#[test_case(r#"{{< quote source="Rom 8:38-39" >}}neither death, nor life ... will be able to separate us from God's love{{< /quote >}}"#, vec![]; "ellipsis")]
+#[test_case(r#"{{< quote source="John 11:35" translation="web" >}}<em>Jesus</em> wept.{{< /quote >}}"#, vec![]; "translation and markup")]
+#[test_case(r#"{{< quote source="Rom 8:38" >}}{{< /quote >}}"#, vec![]; "empty quote")]
+#[test_case(r#"{{< quote source="Rom 8:39 and John 11:35" >}}Jesus wept. Nor height{{< /quote >}}"#, vec![]; "several books in canonical order")]
+#[test_case(r#"{{< quote source="Rom 8:38" >}}nor height, nor depth, nor any other created thing{{< /quote >}}"#,
+    vec![(4, "Rom 8:38", "matches only 33% of its words to the cited verses")]; "wrong verse")]
+#[test_case("Some text\n\n{{< quote source=\"John 11:35\" translation=\"KJV\" >}}Jesus wept.{{< /quote >}}",
+    vec![(6, "John 11:35", "has no text for translation KJV")]; "unknown translation")]
+#[test_case(r#"{{< quote source="Rom 9:1" >}}I tell the truth{{< /quote >}}"#,
+    vec![(4, "Rom 9:1", "has no text for the cited verses")]; "missing verses")]
+#[test_case(r#"{{< quote source="the prophet" >}}I tell the truth{{< /quote >}}"#,
+    vec![(4, "the prophet", "has no reference")]; "not a reference")]
+fn test_mismatched_quotes(text: &str, expected: Vec<(usize, &str, &str)>) {
+    let (_dir, texts) = texts();
+    let result = mismatched_quotes(HEADER, text, &texts, 80).unwrap();
+
+    assert_eq!(
+        result,
+        expected
+            .into_iter()
+            .map(|(line, source, problem)| Mismatch {
+                line,
+                source: source.to_string(),
+                problem: problem.to_string()
+            })
+            .collect::<Vec<Mismatch>>()
+    );
+}
+
+#[test_case("God's love—in Christ", vec!["gods", "love", "in", "christ"]; "punctuation")]
+#[test_case("<em>Jesus</em> wept {{< br >}}", vec!["jesus", "wept"]; "markup")]
+fn test_words(text: &str, expected: Vec<&str>) {
+    assert_eq!(words(text), expected);
+}
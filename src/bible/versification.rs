@@ -0,0 +1,461 @@
+use super::books::chapter_verse_counts;
+use super::*;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+/// A versification scheme, that is, the chapter and verse numbering used by a translation.
+///
+/// English is the canonical scheme, onto which all references are mapped before indexing.
+#[derive(Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[serde(try_from = "String")]
+pub enum Scheme {
+    #[default]
+    English,
+    /// Masoretic text, as used by Hebrew bibles
+    Hebrew,
+    /// Septuagint and Vulgate, which follow the Hebrew verse numbering in the Psalms but number the
+    /// Psalms themselves differently;  other books are taken to follow the English numbering
+    Septuagint,
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct UnknownSchemeError(String);
+
+impl Display for UnknownSchemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "unknown versification scheme '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownSchemeError {}
+
+impl FromStr for Scheme {
+    type Err = UnknownSchemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Scheme::*;
+
+        match s.to_lowercase().as_str() {
+            "english" | "en" => Ok(English),
+            "hebrew" | "mt" => Ok(Hebrew),
+            "septuagint" | "lxx" | "vulgate" => Ok(Septuagint),
+            _ => Err(UnknownSchemeError(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Scheme {
+    type Error = UnknownSchemeError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Scheme::from_str(&s)
+    }
+}
+
+/// a contiguous run of verses in one chapter which is numbered differently in the English scheme
+struct Shift {
+    chapter: CInt,
+    verses: (VInt, VInt),
+    // chapter and first verse in the target scheme
+    to: (CInt, VInt),
+}
+
+impl Shift {
+    fn map(&self, chapter: CInt, verse: VInt) -> Option<(CInt, VInt)> {
+        if chapter == self.chapter && verse >= self.verses.0 && verse <= self.verses.1 {
+            Some((self.to.0, self.to.1 + verse - self.verses.0))
+        } else {
+            None
+        }
+    }
+}
+
+fn shift(chapter: CInt, verses: (VInt, VInt), to: (CInt, VInt)) -> Shift {
+    Shift {
+        chapter,
+        verses,
+        to,
+    }
+}
+
+/// Hebrew chapter boundaries which differ from the English, outside of the Psalms
+fn hebrew_shifts() -> &'static HashMap<&'static str, Vec<Shift>> {
+    lazy_static! {
+        static ref SHIFTS: HashMap<&'static str, Vec<Shift>> = HashMap::from([
+            (
+                "Genesis",
+                vec![shift(32, (1, 1), (31, 55)), shift(32, (2, 33), (32, 1))]
+            ),
+            (
+                "Exodus",
+                vec![
+                    shift(7, (26, 29), (8, 1)),
+                    shift(8, (1, 28), (8, 5)),
+                    shift(21, (37, 37), (22, 1)),
+                    shift(22, (1, 30), (22, 2)),
+                ]
+            ),
+            (
+                "Leviticus",
+                vec![shift(5, (20, 26), (6, 1)), shift(6, (1, 23), (6, 8))]
+            ),
+            (
+                "Numbers",
+                vec![
+                    shift(17, (1, 15), (16, 36)),
+                    shift(17, (16, 28), (17, 1)),
+                    shift(30, (1, 1), (29, 40)),
+                    shift(30, (2, 17), (30, 1)),
+                ]
+            ),
+            (
+                "Deuteronomy",
+                vec![
+                    shift(13, (1, 1), (12, 32)),
+                    shift(13, (2, 19), (13, 1)),
+                    shift(23, (1, 1), (22, 30)),
+                    shift(23, (2, 26), (23, 1)),
+                    shift(28, (69, 69), (29, 1)),
+                    shift(29, (1, 28), (29, 2)),
+                ]
+            ),
+            (
+                "1 Samuel",
+                vec![
+                    shift(21, (1, 1), (20, 42)),
+                    shift(21, (2, 16), (21, 1)),
+                    shift(24, (1, 1), (23, 29)),
+                    shift(24, (2, 23), (24, 1)),
+                ]
+            ),
+            (
+                "2 Samuel",
+                vec![shift(19, (1, 1), (18, 33)), shift(19, (2, 44), (19, 1))]
+            ),
+            (
+                "1 Kings",
+                vec![shift(5, (1, 14), (4, 21)), shift(5, (15, 32), (5, 1))]
+            ),
+            (
+                "2 Kings",
+                vec![shift(12, (1, 1), (11, 21)), shift(12, (2, 22), (12, 1))]
+            ),
+            (
+                "1 Chronicles",
+                vec![shift(5, (27, 41), (6, 1)), shift(6, (1, 66), (6, 16))]
+            ),
+            (
+                "2 Chronicles",
+                vec![
+                    shift(1, (18, 18), (2, 1)),
+                    shift(2, (1, 17), (2, 2)),
+                    shift(13, (23, 23), (14, 1)),
+                    shift(14, (1, 14), (14, 2)),
+                ]
+            ),
+            (
+                "Nehemiah",
+                vec![
+                    shift(3, (33, 38), (4, 1)),
+                    shift(4, (1, 17), (4, 7)),
+                    shift(10, (1, 1), (9, 38)),
+                    shift(10, (2, 40), (10, 1)),
+                ]
+            ),
+            (
+                "Job",
+                vec![shift(40, (25, 32), (41, 1)), shift(41, (1, 26), (41, 9))]
+            ),
+            (
+                "Ecclesiastes",
+                vec![shift(4, (17, 17), (5, 1)), shift(5, (1, 19), (5, 2))]
+            ),
+            (
+                "Song of Solomon",
+                vec![shift(7, (1, 1), (6, 13)), shift(7, (2, 14), (7, 1))]
+            ),
+            (
+                "Isaiah",
+                vec![shift(8, (23, 23), (9, 1)), shift(9, (1, 20), (9, 2))]
+            ),
+            (
+                "Jeremiah",
+                vec![shift(8, (23, 23), (9, 1)), shift(9, (1, 25), (9, 2))]
+            ),
+            (
+                "Ezekiel",
+                vec![shift(21, (1, 5), (20, 45)), shift(21, (6, 37), (21, 1))]
+            ),
+            (
+                "Daniel",
+                vec![
+                    shift(3, (31, 33), (4, 1)),
+                    shift(4, (1, 34), (4, 4)),
+                    shift(6, (1, 1), (5, 31)),
+                    shift(6, (2, 29), (6, 1)),
+                ]
+            ),
+            (
+                "Hosea",
+                vec![
+                    shift(2, (1, 2), (1, 10)),
+                    shift(2, (3, 25), (2, 1)),
+                    shift(12, (1, 1), (11, 12)),
+                    shift(12, (2, 15), (12, 1)),
+                    shift(14, (1, 1), (13, 16)),
+                    shift(14, (2, 10), (14, 1)),
+                ]
+            ),
+            (
+                "Joel",
+                vec![shift(3, (1, 5), (2, 28)), shift(4, (1, 21), (3, 1))]
+            ),
+            (
+                "Jonah",
+                vec![shift(2, (1, 1), (1, 17)), shift(2, (2, 11), (2, 1))]
+            ),
+            (
+                "Micah",
+                vec![shift(4, (14, 14), (5, 1)), shift(5, (1, 14), (5, 2))]
+            ),
+            (
+                "Nahum",
+                vec![shift(2, (1, 1), (1, 15)), shift(2, (2, 14), (2, 1))]
+            ),
+            (
+                "Zechariah",
+                vec![shift(2, (1, 4), (1, 18)), shift(2, (5, 17), (2, 1))]
+            ),
+            ("Malachi", vec![shift(3, (19, 24), (4, 1))]),
+        ]);
+    }
+
+    &SHIFTS
+}
+
+/// Septuagint Psalms numbered differently from the Hebrew, mapped onto the Hebrew
+fn septuagint_psalm_shifts() -> &'static Vec<Shift> {
+    lazy_static! {
+        static ref SHIFTS: Vec<Shift> = vec![
+            shift(9, (22, 39), (10, 1)),
+            shift(113, (1, 8), (114, 1)),
+            shift(113, (9, 26), (115, 1)),
+            shift(114, (1, 9), (116, 1)),
+            shift(115, (1, 10), (116, 10)),
+            shift(146, (1, 11), (147, 1)),
+            shift(147, (1, 9), (147, 12)),
+        ];
+    }
+
+    &SHIFTS
+}
+
+/// number of verses by which the Hebrew numbering of a psalm is ahead of the English,
+/// because the superscription is counted as a verse
+fn hebrew_psalm_superscription(psalm: CInt) -> VInt {
+    match psalm {
+        51 | 52 | 54 | 60 => 2,
+        3..=9
+        | 12
+        | 13
+        | 18..=22
+        | 30
+        | 31
+        | 34
+        | 36
+        | 38..=42
+        | 44..=49
+        | 53
+        | 55..=59
+        | 61..=65
+        | 67..=70
+        | 75..=77
+        | 80
+        | 81
+        | 83..=85
+        | 88
+        | 89
+        | 92
+        | 102
+        | 108
+        | 140
+        | 142 => 1,
+        _ => 0,
+    }
+}
+
+fn septuagint_psalm_to_hebrew(chapter: CInt, verse: VInt) -> (CInt, VInt) {
+    if let Some(mapped) = septuagint_psalm_shifts()
+        .iter()
+        .find_map(|s| s.map(chapter, verse))
+    {
+        mapped
+    } else {
+        match chapter {
+            10..=112 | 116..=145 => (chapter + 1, verse),
+            _ => (chapter, verse),
+        }
+    }
+}
+
+fn hebrew_to_english(book: &str, chapter: CInt, verse: VInt) -> (CInt, VInt) {
+    if book == "Psalms" {
+        let offset = hebrew_psalm_superscription(chapter);
+        // the superscription itself is taken to be part of the first verse
+        (chapter, verse.saturating_sub(offset).max(1))
+    } else {
+        hebrew_shifts()
+            .get(book)
+            .and_then(|shifts| shifts.iter().find_map(|s| s.map(chapter, verse)))
+            .unwrap_or((chapter, verse))
+    }
+}
+
+impl Scheme {
+    /// map a single verse in this scheme onto the English scheme
+    fn canonical_verse(&self, book: &str, chapter: CInt, verse: VInt) -> (CInt, VInt) {
+        use Scheme::*;
+
+        match self {
+            English => (chapter, verse),
+            Hebrew => hebrew_to_english(book, chapter, verse),
+            Septuagint => {
+                if book == "Psalms" {
+                    let (chapter, verse) = septuagint_psalm_to_hebrew(chapter, verse);
+                    hebrew_to_english(book, chapter, verse)
+                } else {
+                    (chapter, verse)
+                }
+            }
+        }
+    }
+
+    /// the shifts applied to the book before any general renumbering
+    fn shifts(&self, book: &str) -> &'static [Shift] {
+        use Scheme::*;
+
+        match self {
+            English => &[],
+            Hebrew => hebrew_shifts()
+                .get(book)
+                .map(|shifts| shifts.as_slice())
+                .unwrap_or(&[]),
+            Septuagint if book == "Psalms" => septuagint_psalm_shifts(),
+            Septuagint => &[],
+        }
+    }
+
+    /// The verses of a chapter in this scheme, as far as the English verse counts and the shifts show.
+    ///
+    /// A verse which isn't shifted is taken not to exist if its English namesake is reached by a shift,
+    /// as for Hebrew Joel 3:6, since Hebrew Joel 3 is English Joel 2:28-32.
+    fn chapter_verses(&self, book: &str, chapter: CInt) -> impl Iterator<Item = VInt> + '_ {
+        let shifts = self.shifts(book);
+        let english_count = chapter_verse_counts(book)
+            .and_then(|counts| counts.get((chapter as usize).checked_sub(1)?))
+            .copied()
+            .unwrap_or(0);
+        let shifted_count = shifts
+            .iter()
+            .filter(|s| s.chapter == chapter)
+            .map(|s| s.verses.1)
+            .max()
+            .unwrap_or(0);
+
+        (1..=english_count.max(shifted_count)).filter(move |verse| {
+            shifts.iter().any(|s| s.map(chapter, *verse).is_some())
+                || !shifts.iter().any(|s| {
+                    s.to.0 == chapter
+                        && *verse >= s.to.1
+                        && *verse <= s.to.1 + (s.verses.1 - s.verses.0)
+                })
+        })
+    }
+
+    /// Map a chapter without verses onto the English scheme, where it may reach several chapters.
+    ///
+    /// An English chapter is cited whole only if every one of its verses is reached, and otherwise
+    /// only for the verses reached, so Hebrew Joel 3 is English Joel 2:28-32.
+    fn canonical_whole_chapter(&self, book: &str, chapter: CInt) -> Vec<ChapterVerses> {
+        let english_counts = chapter_verse_counts(book).unwrap_or_default();
+        let mut verses_by_chapter = BTreeMap::<CInt, BTreeSet<VInt>>::new();
+
+        for verse in self.chapter_verses(book, chapter) {
+            let (c, v) = self.canonical_verse(book, chapter, verse);
+            verses_by_chapter.entry(c).or_default().insert(v);
+        }
+
+        verses_by_chapter
+            .into_iter()
+            .map(|(c, verses)| {
+                let english_count = (c as usize)
+                    .checked_sub(1)
+                    .and_then(|i| english_counts.get(i))
+                    .copied()
+                    .unwrap_or(0);
+                let whole = english_count > 0 && (1..=english_count).all(|v| verses.contains(&v));
+
+                let mut vspans = VSpans::new();
+                if !whole {
+                    for v in verses {
+                        vspans.insert(VSpan::at(v));
+                    }
+                }
+                ChapterVerses::new(Some(Chapter(c)), vspans)
+            })
+            .collect()
+    }
+
+    /// map the chapter and verses in this scheme onto the English scheme, which may span several chapters
+    fn canonical_chapter_verses(&self, book: &str, cv: ChapterVerses) -> Vec<ChapterVerses> {
+        match cv.chapter {
+            // single chapter books are numbered the same everywhere
+            None => vec![cv],
+            Some(Chapter(chapter)) if cv.verses.is_empty() => {
+                self.canonical_whole_chapter(book, chapter)
+            }
+            Some(Chapter(chapter)) => {
+                let mut verses_by_chapter = BTreeMap::<CInt, VSpans>::new();
+
+                for span in &cv.verses {
+                    for verse in span.lower()..=span.upper() {
+                        let (c, v) = self.canonical_verse(book, chapter, verse);
+                        verses_by_chapter
+                            .entry(c)
+                            .or_insert_with(VSpans::new)
+                            .insert(VSpan::at(v));
+                    }
+                }
+
+                verses_by_chapter
+                    .into_iter()
+                    .map(|(c, verses)| ChapterVerses::new(Some(Chapter(c)), verses))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl References {
+    /// map all references from the given scheme onto the English scheme
+    pub fn into_canonical(self, scheme: Scheme) -> References {
+        if scheme == Scheme::English {
+            return self;
+        }
+
+        let mut canonical = References::new();
+
+        for (book, cvs) in self {
+            for cv in cvs {
+                for canonical_cv in scheme.canonical_chapter_verses(book, cv) {
+                    canonical.insert(book, canonical_cv);
+                }
+            }
+        }
+
+        canonical
+    }
+}
+
+mod tests;
@@ -0,0 +1,53 @@
+#![cfg(test)]
+
+use super::super::extraction::references;
+use super::*;
+use test_case::test_case;
+
+#[test]
+fn test_scheme_from_str() {
+    assert_eq!(Scheme::from_str("English"), Ok(Scheme::English));
+    assert_eq!(Scheme::from_str("MT"), Ok(Scheme::Hebrew));
+    assert_eq!(Scheme::from_str("vulgate"), Ok(Scheme::Septuagint));
+    assert!(Scheme::from_str("klingon").is_err());
+}
+
+#[test_case(Scheme::Hebrew, "Malachi", 3, 19, (4, 1); "hebrew malachi")]
+#[test_case(Scheme::Hebrew, "Malachi", 3, 18, (3, 18); "hebrew malachi unshifted")]
+#[test_case(Scheme::Hebrew, "Joel", 3, 1, (2, 28); "hebrew joel 3")]
+#[test_case(Scheme::Hebrew, "Joel", 4, 21, (3, 21); "hebrew joel 4")]
+#[test_case(Scheme::Hebrew, "Psalms", 51, 3, (51, 1); "hebrew psalm double superscription")]
+#[test_case(Scheme::Hebrew, "Psalms", 3, 1, (3, 1); "hebrew psalm superscription itself")]
+#[test_case(Scheme::Hebrew, "Psalms", 23, 1, (23, 1); "hebrew psalm no superscription")]
+#[test_case(Scheme::Septuagint, "Psalms", 22, 2, (23, 2); "septuagint psalm renumbered")]
+#[test_case(Scheme::Septuagint, "Psalms", 9, 22, (10, 1); "septuagint psalm split")]
+#[test_case(Scheme::Septuagint, "Psalms", 50, 3, (51, 1); "septuagint psalm superscription")]
+#[test_case(Scheme::Septuagint, "Joel", 2, 28, (2, 28); "septuagint outside psalms")]
+fn test_canonical_verse(
+    scheme: Scheme,
+    book: &str,
+    chapter: CInt,
+    verse: VInt,
+    expected: (CInt, VInt),
+) {
+    assert_eq!(scheme.canonical_verse(book, chapter, verse), expected);
+}
+
+#[test_case(Scheme::English, "Malachi 3:19-24", "Malachi 3:19-24"; "english unchanged")]
+#[test_case(Scheme::Hebrew, "Malachi 3:16-21", "Malachi 3:16-18; 4:1-3"; "hebrew across chapters")]
+#[test_case(Scheme::Hebrew, "Joel 3", "Joel 2:28-32"; "hebrew chapter only")]
+#[test_case(Scheme::Hebrew, "Malachi 3", "Malachi 3; 4"; "hebrew chapter only spilling into the next")]
+#[test_case(Scheme::Hebrew, "Genesis 32", "Genesis 31:55; 32"; "hebrew chapter only spilling into the previous")]
+#[test_case(Scheme::Hebrew, "Exodus 8", "Exodus 8:5-32"; "hebrew chapter only wholly shifted")]
+#[test_case(Scheme::Hebrew, "Joel 4", "Joel 3"; "hebrew chapter only beyond english")]
+#[test_case(Scheme::Septuagint, "Psalm 9", "Psalms 9; 10"; "septuagint psalm chapter only")]
+#[test_case(Scheme::Septuagint, "Psalm 113", "Psalms 114; 115"; "septuagint psalm chapter only spanning two")]
+#[test_case(Scheme::Hebrew, "Psalm 51:3-4, 12", "Psalms 51:1-2,10"; "hebrew psalm verses")]
+#[test_case(Scheme::Septuagint, "Psalm 113:1-10", "Psalms 114:1-8; 115:1-2"; "septuagint psalm split")]
+fn test_references_into_canonical(scheme: Scheme, src: &str, expected: &str) {
+    let (refs, _) = references("", src);
+    let refs = refs.into_canonical(scheme);
+    let (book, cvs) = refs.into_iter().next().unwrap();
+
+    assert_eq!(format!("{} {}", book, cvs), expected);
+}
@@ -0,0 +1,53 @@
+use crate::bible::{Scheme, StyleConfig};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// project configuration, read from `lta.toml` alongside the content directory
+#[derive(Deserialize, Default, PartialEq, Eq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// versification scheme for posts which don't declare one in their front matter
+    pub versification: Scheme,
+    /// citation style for the scripture index
+    pub style: StyleConfig,
+    /// citation style for `normalize-references`
+    pub normalize: StyleConfig,
+    /// Bible text store created by `import-text`, relative to this file
+    pub text: Option<PathBuf>,
+    /// name of the translation in the default Bible text store
+    pub translation: Option<String>,
+    /// Bible text stores by translation name, for quotes which name their translation, relative to this file
+    pub texts: HashMap<String, PathBuf>,
+    /// maximum length of verse excerpts in the scripture index
+    pub excerpt_length: Option<usize>,
+}
+
+impl Config {
+    /// load the config, which is all defaults if the file doesn't exist
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                Config::from_toml(&text).context(format!("parse(\"{}\")", path.as_ref().display()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => {
+                Err(anyhow::Error::from(e)
+                    .context(format!("read(\"{}\")", path.as_ref().display())))
+            }
+        }
+    }
+
+    fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str::<Config>(text)?)
+    }
+}
+
+mod tests;
@@ -0,0 +1,24 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_config_from_toml() {
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    assert_eq!(
+        Config::from_toml(r#"versification = "lxx""#).unwrap(),
+        Config {
+            versification: Scheme::Septuagint,
+            ..Config::default()
+        }
+    );
+    assert_eq!(
+        Config::from_toml("text = \"text/web\"\nexcerpt_length = 60").unwrap(),
+        Config {
+            text: Some(PathBuf::from("text/web")),
+            excerpt_length: Some(60),
+            ..Config::default()
+        }
+    );
+    assert!(Config::from_toml(r#"versification = "klingon""#).is_err());
+}
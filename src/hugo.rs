@@ -6,6 +6,9 @@ pub trait Create {
 
     // TODO return URL type not String
     fn create_leaf(&mut self, header: &Header) -> Result<(File, String)>;
+
+    /// a section within this one, whose branch page has the header, and its URL
+    fn create_section(&mut self, header: &Header) -> Result<(Box<dyn Create>, String)>;
 }
 
 mod content;
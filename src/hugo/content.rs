@@ -38,6 +38,9 @@ pub struct Header {
     pub title: Option<String>,
     pub description: Option<String>,
     pub date: Option<String>,
+    pub versification: Option<String>,
+    /// translation quoted in the post, unless a quote names its own
+    pub translation: Option<String>,
 }
 
 impl Header {
@@ -46,6 +49,8 @@ impl Header {
             title: Some(title.to_owned()),
             description: Some(description.to_owned()),
             date: None,
+            versification: None,
+            translation: None,
         }
     }
 }
@@ -62,6 +67,12 @@ impl Metadata {
         Metadata { path, url, header }
     }
 
+    /// The path of the page within the content directory, as Hugo's `.File.Path`,
+    /// by which data files key their posts so a theme can look up the page it's rendering.
+    pub fn file_path(&self) -> &str {
+        self.url.trim_start_matches('/')
+    }
+
     pub fn format_href(&self, sequence_number: &Option<usize>) -> String {
         let title = self.header.title.as_deref().unwrap_or("Unknown");
         match sequence_number {
@@ -84,6 +95,12 @@ pub fn format_href(text: &str, url: &str) -> String {
 
 const CONTENT_DIR: &str = "content";
 
+/// project configuration file, which lives alongside the content directory
+const CONFIG_FILE: &str = "lta.toml";
+
+/// Hugo data files, which live alongside the content directory
+const DATA_DIR: &str = "data";
+
 /// where Hugo posts live
 pub const POSTS_SECTION: &str = "post";
 
@@ -124,6 +141,15 @@ impl Content {
     pub fn section_writer(&self, section: &'static str) -> anyhow::Result<ContentWriter> {
         ContentWriter::new(&self.root, section)
     }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join("..").join(CONFIG_FILE)
+    }
+
+    /// path of the named file in the data directory, whose name is its key in Hugo's site data
+    pub fn data_path(&self, name: &str) -> PathBuf {
+        self.root.join("..").join(DATA_DIR).join(name)
+    }
 }
 
 pub struct IntoIter<T, F>
@@ -222,7 +248,8 @@ fn header_and_body(text: &str) -> Result<(Header, &str, &str)> {
 
 #[derive(Debug)]
 pub struct ContentWriter {
-    section: &'static str,
+    /// path of the section below the content root, as in its URL
+    section: String,
     section_dir: PathBuf,
     branch_yaml_header: String,
 }
@@ -240,7 +267,7 @@ impl ContentWriter {
         fs::create_dir_all(&section_dir)?;
 
         Ok(ContentWriter {
-            section,
+            section: section.to_string(),
             section_dir,
             branch_yaml_header,
         })
@@ -274,6 +301,12 @@ impl super::Create for ContentWriter {
         let path = self.section_dir.join(format!("{}.md", slug));
         let url = format!("/{}/{}", self.section, slug);
 
+        // a section of the same name from an earlier run would have the same URL
+        let section_dir = self.section_dir.join(&slug);
+        if section_dir.exists() {
+            fs::remove_dir_all(&section_dir)?;
+        }
+
         let mut f = File::create(path)?;
         f.write_all(
             // TODO use YAML serializer
@@ -288,6 +321,39 @@ impl super::Create for ContentWriter {
 
         Ok((f, url))
     }
+
+    fn create_section(
+        &mut self,
+        header: &Header,
+    ) -> anyhow::Result<(Box<dyn super::Create>, String)> {
+        let unknown_title = "Unknown".to_string();
+        let unknown_description = "".to_string();
+        let title = header.title.as_ref().unwrap_or(&unknown_title);
+        let description = header.description.as_ref().unwrap_or(&unknown_description);
+        let slug = slug::slugify(title);
+        let section_dir = self.section_dir.join(&slug);
+        let url = format!("/{}/{}", self.section, slug);
+
+        // as would a leaf of the same name from an earlier run
+        let leaf_path = self.section_dir.join(format!("{}.md", slug));
+        if leaf_path.exists() {
+            fs::remove_file(&leaf_path)?;
+        }
+        fs::create_dir_all(&section_dir)?;
+
+        Ok((
+            Box::new(ContentWriter {
+                section: format!("{}/{}", self.section, slug),
+                section_dir,
+                // TODO use YAML serializer
+                branch_yaml_header: format!(
+                    "title: \"{}\"\ndescription: \"{}\"\n",
+                    title, description
+                ),
+            }),
+            url,
+        ))
+    }
 }
 
 /// write a table;  it is the callers responsibility to ensure that all rows are the same length, and match the header
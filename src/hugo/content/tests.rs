@@ -23,7 +23,9 @@ fn test_header_and_body() {
             Header {
                 title: Some("My Title".to_string()),
                 description: None,
-                date: Some("2023-08-30T06:25:00+12:00".to_string())
+                date: Some("2023-08-30T06:25:00+12:00".to_string()),
+                versification: None,
+                translation: None,
             },
             r###"
     +++
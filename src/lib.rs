@@ -0,0 +1,4 @@
+pub mod bible;
+pub mod config;
+pub mod hugo;
+mod util;
@@ -1,12 +1,22 @@
-use anyhow::Result;
-use bible::AllReferences;
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, Result};
+use bible::{
+    AllReferences, Excerpts, IndexOptions, Lectionary, Parallels, Pericopes, Preset, RowOrder,
+    Scheme, SinglePage, TextFormat, TextStore, Texts,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use lta::{bible, config::Config, hugo};
 use std::{
-    fs::File,
-    io::{stderr, Write},
-    path::PathBuf,
+    fs::{self, File},
+    io::{stderr, stdout, Write},
+    path::{Path, PathBuf},
     process::ExitCode,
+    str::FromStr,
 };
+use time::OffsetDateTime;
+
+const REF_SECTION: &str = "ref";
+const RELATED_POSTS_DATA: &str = "related_posts.json";
+const SCRIPTURE_DATA: &str = "scripture";
 
 #[derive(Parser)]
 struct Cli {
@@ -16,26 +26,205 @@ struct Cli {
     command: Commands,
 }
 
+#[derive(Args)]
+struct ScriptureIndexArgs {
+    #[arg(long)]
+    with_sequence_numbers: bool,
+    /// citation style, overriding any configured preset
+    #[arg(long, value_enum)]
+    style: Option<Preset>,
+    /// also list posts under passages parallel to those they cite, from this file
+    #[arg(long)]
+    parallels: Option<PathBuf>,
+    /// annotate each reference with the section headings it falls in, from this file
+    #[arg(long)]
+    pericopes: Option<PathBuf>,
+    /// include an excerpt of each passage, from the configured text store unless given here
+    #[arg(long)]
+    excerpts: bool,
+    /// Bible text store created by import-text
+    #[arg(long)]
+    text: Option<PathBuf>,
+    /// show the translations quoted by each post
+    #[arg(long)]
+    with_translations: bool,
+    /// give books with more than this many rows a section, with a page for each chapter
+    #[arg(long, value_name = "ROWS")]
+    chapter_pages_over: Option<usize>,
+    /// order of rows on each book page
+    #[arg(long, value_enum, default_value_t)]
+    order: RowOrder,
+    /// add a column for the date of each post
+    #[arg(long)]
+    with_dates: bool,
+    /// put the whole index on the section page, or on a page with this title
+    #[arg(long, value_name = "TITLE")]
+    single_page: Option<Option<String>>,
+    /// add a heatmap of how much of each book and chapter the posts cite
+    #[arg(long)]
+    with_coverage: bool,
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    CreateScriptureIndex {
+    CreateScriptureIndex(ScriptureIndexArgs),
+    CreateScriptureIndexLinks,
+    /// suggest passages related to the given one, with any posts which already cite them
+    CrossReferences {
+        /// cross-reference dataset, as tab-separated OSIS references with votes
+        #[arg(short, long)]
+        file: PathBuf,
+        /// maximum number of related passages to list
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+        passage: String,
+    },
+    /// write tab-separated post URL and OSIS reference for every reference
+    ExportOsis {
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// add a column for the translations quoted by the post
         #[arg(long)]
-        with_sequence_numbers: bool,
+        with_translations: bool,
     },
-    CreateScriptureIndexLinks,
+    /// write a page of lectionary days with the posts which cite their readings
+    CreateLectionaryPage {
+        /// lectionary, as tab-separated day and readings
+        #[arg(short, long)]
+        file: PathBuf,
+    },
+    /// import a Bible translation into a text store
+    ImportText {
+        #[arg(long, value_enum)]
+        format: TextFormat,
+        /// text store directory
+        #[arg(short, long)]
+        output: PathBuf,
+        /// file to import, or directory of files
+        input: PathBuf,
+    },
+    NormalizeReferences {
+        /// citation style, overriding any configured preset
+        #[arg(long, value_enum)]
+        style: Option<Preset>,
+    },
+    /// print the text of the passage from a text store
+    PrintText {
+        /// text store directory
+        #[arg(short, long)]
+        store: PathBuf,
+        passage: String,
+    },
+    /// check that quoted text comes from the verses cited as its source
+    CheckQuotes {
+        /// default Bible text store, for quotes which don't name their translation
+        #[arg(long)]
+        text: Option<PathBuf>,
+        /// minimum percentage of quoted words which must match the cited verses
+        #[arg(long, default_value_t = 80, value_parser = clap::value_parser!(u8).range(0..=100))]
+        threshold: u8,
+    },
+    /// fill in empty quotes with the text of their source
+    FillQuotes {
+        /// translation to quote, if not the configured default
+        #[arg(long)]
+        translation: Option<String>,
+    },
+    /// report statistics of the citations in posts
+    Stats {
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// maximum number of most cited books, chapters, verses and passages to list
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// list the books and chapters never cited, or not cited for a while
+    Gaps {
+        /// also list chapters last cited more than this many months ago
+        #[arg(long)]
+        months: Option<u32>,
+        /// suggest a series of posts covering the gaps, least cited books first
+        #[arg(long)]
+        plan: bool,
+    },
+    /// report on the layout of the scripture index: posts split across rows, posts with most rows,
+    /// and interrupted books, where some posts couldn't be merged into a single row because another
+    /// post cites a chapter in between (this replaces the books which fell back to appending rows,
+    /// since merging no longer has a fallback)
+    Report {
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// also list posts under passages parallel to those they cite, from this file, as in the index
+        #[arg(long)]
+        parallels: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// maximum number of posts with most rows to list
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// write the posts related to each post by the passages they both cite, as Hugo data
+    CreateRelatedPosts {
+        /// maximum number of related posts for each post
+        #[arg(short, long, default_value_t = 5)]
+        limit: usize,
+    },
+    /// write the scripture index, each post's references and sequence numbers as Hugo data
+    CreateScriptureData,
     ContextualizeHomeLinks,
 }
 
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum OutputFormat {
+    /// readable tables
+    #[default]
+    Table,
+    Json,
+}
+
 fn main() -> ExitCode {
     use Commands::*;
 
     let cli = Cli::parse();
 
     let result = match &cli.command {
-        CreateScriptureIndex {
-            with_sequence_numbers,
-        } => create_scripture_index(*with_sequence_numbers),
+        CreateScriptureIndex(args) => create_scripture_index(args),
         CreateScriptureIndexLinks => create_scripture_index_links(),
+        CrossReferences {
+            file,
+            limit,
+            passage,
+        } => cross_references(file, *limit, passage),
+        ExportOsis {
+            output,
+            with_translations,
+        } => export_osis(output.as_ref(), *with_translations),
+        CreateLectionaryPage { file } => create_lectionary_page(file),
+        ImportText {
+            format,
+            output,
+            input,
+        } => import_text(*format, input, output),
+        NormalizeReferences { style } => normalize_references(*style),
+        PrintText { store, passage } => print_text(store, passage),
+        CheckQuotes { text, threshold } => check_quotes(text.as_ref(), *threshold),
+        FillQuotes { translation } => fill_quotes(translation.as_deref()),
+        Stats {
+            output,
+            format,
+            limit,
+        } => stats(output.as_ref(), *format, *limit),
+        Gaps { months, plan } => gaps(*months, *plan),
+        Report {
+            output,
+            parallels,
+            format,
+            limit,
+        } => report(output.as_ref(), parallels.as_ref(), *format, *limit),
+        CreateRelatedPosts { limit } => create_related_posts(*limit),
+        CreateScriptureData => create_scripture_data(),
         ContextualizeHomeLinks => contextualize_home_links(),
     };
 
@@ -47,30 +236,141 @@ fn main() -> ExitCode {
     }
 }
 
-fn create_scripture_index(with_sequence_numbers: bool) -> Result<()> {
-    let content = hugo::Content::new()?;
+/// the versification scheme declared by the post, falling back to the configured default
+fn versification_scheme(post_metadata: &hugo::Metadata, config: &Config) -> Result<Scheme> {
+    match &post_metadata.header.versification {
+        Some(scheme) => Ok(Scheme::from_str(scheme)?),
+        None => Ok(config.versification),
+    }
+}
+
+/// a path from the config file, which is relative to that file
+fn config_relative(content: &hugo::Content, path: &Path) -> PathBuf {
+    let config_path = content.config_path();
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    config_dir.join(path)
+}
+
+/// the text store given explicitly, or else the configured one
+fn text_store(
+    content: &hugo::Content,
+    config: &Config,
+    explicit_store: Option<&PathBuf>,
+) -> Result<TextStore> {
+    match (explicit_store, &config.text) {
+        (Some(store), _) => TextStore::open(store),
+        (None, Some(store)) => TextStore::open(config_relative(content, store)),
+        (None, None) => Err(anyhow!("no text store given or configured")),
+    }
+}
+
+/// text stores for the configured translations, by case-insensitive translation name
+fn configured_texts(
+    content: &hugo::Content,
+    config: &Config,
+    default_store: Option<TextStore>,
+) -> Result<Texts> {
+    let mut texts = Texts::new(default_store);
+    if let (Some(translation), Some(store)) = (&config.translation, &config.text) {
+        texts.insert(
+            translation,
+            TextStore::open(config_relative(content, store))?,
+        );
+    }
+    for (translation, store) in config.texts.iter() {
+        texts.insert(
+            translation,
+            TextStore::open(config_relative(content, store))?,
+        );
+    }
+
+    Ok(texts)
+}
+
+/// read all the posts and collect their references, mapped onto the canonical versification
+fn read_references(content: &hugo::Content, config: &Config) -> AllReferences {
     let mut refs = AllReferences::new();
 
-    for r in content.section(hugo::POSTS_SECTION, bible::references) {
+    for r in content.section(hugo::POSTS_SECTION, |raw_header, text| {
+        (
+            bible::references(raw_header, text),
+            bible::quoted_translations(text),
+        )
+    }) {
         match r {
-            Ok((post_metadata, (post_refs, warnings))) => {
+            Ok((post_metadata, ((post_refs, warnings), quoted_translations))) => {
                 let annotated_warnings = warnings
                     .into_iter()
                     .map(|w| format!("{}: {}", &post_metadata.url, w));
                 for w in annotated_warnings {
-                    println!("WARN: {}", &w);
+                    eprintln!("WARN: {}", &w);
                 }
 
-                refs.insert(post_metadata, post_refs);
+                let scheme = versification_scheme(&post_metadata, config).unwrap_or_else(|e| {
+                    eprintln!("WARN: {}: {}", &post_metadata.url, e);
+                    config.versification
+                });
+
+                let quoted_translations = quoted_translations
+                    .into_iter()
+                    .map(|(translation, quoted_refs)| {
+                        (translation, quoted_refs.into_canonical(scheme))
+                    })
+                    .collect();
+
+                refs.insert(
+                    post_metadata,
+                    post_refs.into_canonical(scheme),
+                    quoted_translations,
+                );
             }
-            Err(e) => println!("{:#}", e),
+            Err(e) => eprintln!("{:#}", e),
         }
     }
 
-    const REF_SECTION: &str = "ref";
+    refs
+}
+
+fn create_scripture_index(args: &ScriptureIndexArgs) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let mut refs = read_references(&content, &config);
+
+    if let Some(parallels) = &args.parallels {
+        refs.insert_parallels(&Parallels::load(parallels)?);
+    }
+
+    let pericopes = match &args.pericopes {
+        Some(pericopes) => Pericopes::load(pericopes)?,
+        None => Pericopes::default(),
+    };
+
+    let excerpts = if args.excerpts {
+        let store = text_store(&content, &config, args.text.as_ref())?;
+        let length = config.excerpt_length.unwrap_or(Excerpts::DEFAULT_LENGTH);
+        Some(Excerpts::new(store, length))
+    } else {
+        None
+    };
+
     let cw = content.section_writer(REF_SECTION)?;
 
-    refs.tabulate(Box::new(cw), with_sequence_numbers)?;
+    let options = IndexOptions {
+        style: config.style.resolve(args.style, Preset::House),
+        pericopes: &pericopes,
+        excerpts: excerpts.as_ref(),
+        with_sequence_numbers: args.with_sequence_numbers,
+        with_translations: args.with_translations,
+        chapter_pages_over: args.chapter_pages_over,
+        order: args.order,
+        with_dates: args.with_dates,
+        single_page: args.single_page.as_ref().map(|title| match title {
+            Some(title) => SinglePage::Leaf(title.clone()),
+            None => SinglePage::Branch,
+        }),
+        with_coverage: args.with_coverage,
+    };
+    refs.tabulate(Box::new(cw), options)?;
 
     Ok(())
 }
@@ -94,6 +394,291 @@ fn create_scripture_index_links() -> Result<()> {
     Ok(())
 }
 
+fn cross_references(file: &PathBuf, limit: usize, passage: &str) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+
+    let xrefs = bible::CrossReferences::load(file)?;
+    if xrefs.skipped() > 0 {
+        eprintln!(
+            "WARN: skipped {} unsupported cross-references",
+            xrefs.skipped()
+        );
+    }
+
+    let (passage_refs, _) = bible::references("", passage);
+    if passage_refs.is_empty() {
+        return Err(anyhow!("no passage found in '{}'", passage));
+    }
+
+    for (book, cvs) in passage_refs.iter() {
+        for cv in cvs {
+            println!("\nRelated to {} {}\n", book, cv);
+            let related = xrefs.related(book, cv);
+            bible::write_related(stdout(), &related[..related.len().min(limit)], &refs)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn export_osis(output: Option<&PathBuf>, with_translations: bool) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+
+    let mut w: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(stdout()),
+    };
+
+    for (url, osis_ref, translations) in refs.osis_index() {
+        if with_translations {
+            writeln!(w, "{}\t{}\t{}", url, osis_ref, translations.join(","))?;
+        } else {
+            writeln!(w, "{}\t{}", url, osis_ref)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create_lectionary_page(file: &PathBuf) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+    let lectionary = Lectionary::load(file)?;
+
+    let cw = content.section_writer(REF_SECTION)?;
+    lectionary.write(Box::new(cw), &refs)?;
+
+    Ok(())
+}
+
+fn import_text(format: TextFormat, input: &PathBuf, output: &PathBuf) -> Result<()> {
+    TextStore::import(format, input, output)?;
+
+    Ok(())
+}
+
+fn print_text(store: &PathBuf, passage: &str) -> Result<()> {
+    let store = TextStore::open(store)?;
+
+    let (passage_refs, _) = bible::references("", passage);
+    if passage_refs.is_empty() {
+        return Err(anyhow!("no passage found in '{}'", passage));
+    }
+
+    for (book, cvs) in passage_refs.iter() {
+        for cv in cvs {
+            println!("{} {}", book, cv);
+            for (verse, text) in store.verses(book, cv)? {
+                println!("{} {}", verse, text);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn normalize_references(style: Option<Preset>) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let style = config.normalize.resolve(style, Preset::Chicago);
+
+    for r in content.section(hugo::POSTS_SECTION, |raw_header, text| {
+        bible::normalized(raw_header, text, &style)
+    }) {
+        match r {
+            Ok((post_metadata, post_content)) => {
+                if let Some(post_content) = post_content {
+                    let mut f = File::create(&post_metadata.path)?;
+                    println!("updating {}", post_metadata.path.to_str().unwrap());
+                    f.write_all(post_content.as_bytes())?;
+                }
+            }
+            Err(e) => eprintln!("{:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn check_quotes(text: Option<&PathBuf>, threshold: u8) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+
+    let default_store = match (text, &config.text) {
+        (None, None) => None,
+        _ => Some(text_store(&content, &config, text)?),
+    };
+    let texts = configured_texts(&content, &config, default_store)?;
+
+    let mut mismatch_count = 0;
+    for r in content.section(hugo::POSTS_SECTION, |raw_header, text| {
+        bible::mismatched_quotes(raw_header, text, &texts, threshold)
+    }) {
+        match r {
+            Ok((post_metadata, mismatches)) => {
+                for mismatch in mismatches? {
+                    println!("{}:{}", post_metadata.path.to_str().unwrap(), mismatch);
+                    mismatch_count += 1;
+                }
+            }
+            Err(e) => eprintln!("{:#}", e),
+        }
+    }
+
+    if mismatch_count > 0 {
+        Err(anyhow!(
+            "{} quotes don't match their source",
+            mismatch_count
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn fill_quotes(translation: Option<&str>) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+
+    let translation = translation
+        .or(config.translation.as_deref())
+        .ok_or_else(|| anyhow!("no translation given or configured"))?;
+    let texts = configured_texts(&content, &config, None)?;
+    if texts.get(Some(translation)).is_none() {
+        return Err(anyhow!("no text store configured for {}", translation));
+    }
+
+    for r in content.section(hugo::POSTS_SECTION, |raw_header, text| {
+        bible::with_filled_quotes(raw_header, text, &texts, translation)
+    }) {
+        match r {
+            Ok((post_metadata, filled)) => {
+                let (post_content, warnings) = filled?;
+                for w in warnings {
+                    eprintln!("WARN: {}: {}", &post_metadata.url, w);
+                }
+
+                if let Some(post_content) = post_content {
+                    let mut f = File::create(&post_metadata.path)?;
+                    println!("updating {}", post_metadata.path.to_str().unwrap());
+                    f.write_all(post_content.as_bytes())?;
+                }
+            }
+            Err(e) => eprintln!("{:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn stats(output: Option<&PathBuf>, format: OutputFormat, limit: usize) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+    let style = config.style.resolve(None, Preset::House);
+
+    let mut w: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(stdout()),
+    };
+
+    let statistics = refs.statistics(&style, limit);
+    match format {
+        OutputFormat::Table => statistics.write_tables(&mut w)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut w, &statistics)?;
+            writeln!(w)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn gaps(months: Option<u32>, plan: bool) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+    let style = config.style.resolve(None, Preset::House);
+
+    let today = OffsetDateTime::now_utc().date();
+    let cutoff = months
+        .map(|months| {
+            bible::months_before(today, months)
+                .ok_or_else(|| anyhow!("{} months ago is out of range", months))
+        })
+        .transpose()?;
+    let gaps = refs.gaps(cutoff);
+
+    bible::write_gaps(stdout(), &gaps, cutoff, &style)?;
+    if plan {
+        bible::write_plan(stdout(), &gaps, &style)?;
+    }
+
+    Ok(())
+}
+
+fn report(
+    output: Option<&PathBuf>,
+    parallels: Option<&PathBuf>,
+    format: OutputFormat,
+    limit: usize,
+) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let mut refs = read_references(&content, &config);
+    if let Some(parallels) = parallels {
+        refs.insert_parallels(&Parallels::load(parallels)?);
+    }
+    let style = config.style.resolve(None, Preset::House);
+
+    let mut w: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(stdout()),
+    };
+
+    refs.coalesce();
+    let report = refs.report(&style, limit);
+    match format {
+        OutputFormat::Table => report.write_tables(&mut w)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut w, &report)?;
+            writeln!(w)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create_related_posts(limit: usize) -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let refs = read_references(&content, &config);
+
+    let path = content.data_path(RELATED_POSTS_DATA);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut w = File::create(&path)?;
+    serde_json::to_writer_pretty(&mut w, &refs.related_posts(limit))?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+fn create_scripture_data() -> Result<()> {
+    let content = hugo::Content::new()?;
+    let config = Config::load(content.config_path())?;
+    let mut refs = read_references(&content, &config);
+    let style = config.style.resolve(None, Preset::House);
+
+    refs.scripture_data(&style)
+        .write(content.data_path(SCRIPTURE_DATA))
+}
+
 fn contextualize_home_links() -> Result<()> {
     let docs = hugo::Docs::new()?;
 
@@ -101,7 +686,3 @@ fn contextualize_home_links() -> Result<()> {
 
     Ok(())
 }
-
-mod bible;
-mod hugo;
-mod util;
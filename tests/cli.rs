@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+/// a site with a post citing scripture, one with an unknown versification, and one with a broken header
+fn site() -> TempDir {
+    let site = tempfile::tempdir().unwrap();
+    let posts = site.path().join("content").join("post");
+    fs::create_dir_all(&posts).unwrap();
+
+    fs::write(
+        posts.join("a.md"),
+        "+++\ntitle = \"A\"\ndate = \"2023-08-30T06:00:00Z\"\n+++\n\nSee Romans 8:28 and Genesis 1:1.\n",
+    )
+    .unwrap();
+    fs::write(
+        posts.join("b.md"),
+        "+++\ntitle = \"B\"\nversification = \"klingon\"\n+++\n\nSee Romans 8:28-30.\n",
+    )
+    .unwrap();
+    fs::write(posts.join("c.md"), "+++\ntitle = \n+++\n\nSee John 3:16.\n").unwrap();
+
+    site
+}
+
+fn lta(site: &TempDir, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lta"))
+        .args(args)
+        .current_dir(site.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_stats_json_on_stdout_parses() {
+    let site = site();
+    let output = lta(&site, &["stats", "--format", "json"]);
+
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["posts"], 2);
+    // the warning and the broken header are reported, but not on stdout
+    assert!(String::from_utf8_lossy(&output.stderr).contains("WARN"));
+}
+
+#[test]
+fn test_report_json_on_stdout_parses_and_counts_parallels() {
+    let site = site();
+    let parallels = site.path().join("parallels.tsv");
+    fs::write(&parallels, "Gen.1.1\tJohn.1.1\n").unwrap();
+    let output = lta(
+        &site,
+        &[
+            "report",
+            "--format",
+            "json",
+            "--parallels",
+            parallels.to_str().unwrap(),
+        ],
+    );
+
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    // Romans, Genesis, and John via the parallel
+    assert_eq!(report["most_rows"][0]["title"], "A");
+    assert_eq!(report["most_rows"][0]["rows"], 3);
+}
+
+#[test]
+fn test_chapter_pages_over_threshold() {
+    let site = site();
+    fs::write(
+        site.path().join("content").join("post").join("d.md"),
+        "+++\ntitle = \"D\"\n+++\n\nSee Romans 1:16, 18.\n",
+    )
+    .unwrap();
+    let archetypes = site.path().join("archetypes");
+    fs::create_dir_all(&archetypes).unwrap();
+    fs::write(archetypes.join("ref.yaml"), "title: \"Scripture Index\"\n").unwrap();
+    let refs = site.path().join("content").join("ref");
+
+    // Romans has three rows, over the threshold, so is a section with a page for each chapter
+    let output = lta(
+        &site,
+        &["create-scripture-index", "--chapter-pages-over", "2"],
+    );
+
+    assert!(output.status.success());
+    let romans = fs::read_to_string(refs.join("romans").join("_index.md")).unwrap();
+    assert!(romans.contains("title: \"Romans\""));
+    assert!(
+        romans.contains(r#"<span id="chapter-1"></span>[1]({{<relref "/ref/romans/romans-1" >}})"#)
+    );
+    assert!(
+        romans.contains(r#"<span id="chapter-8"></span>[8]({{<relref "/ref/romans/romans-8" >}})"#)
+    );
+    let romans_8 = fs::read_to_string(refs.join("romans").join("romans-8.md")).unwrap();
+    assert!(romans_8.contains("8:28-30"));
+    assert!(!romans_8.contains("1:16"));
+    assert!(!refs.join("romans.md").exists());
+    assert!(refs.join("genesis.md").exists());
+
+    // not over the threshold, so a single page replacing the section
+    let output = lta(
+        &site,
+        &["create-scripture-index", "--chapter-pages-over", "3"],
+    );
+
+    assert!(output.status.success());
+    let romans = fs::read_to_string(refs.join("romans.md")).unwrap();
+    assert!(romans.contains("1:16"));
+    assert!(romans.contains("8:28-30"));
+    // every verse span cited is anchored, once
+    assert!(romans.contains("{#chapter-8}"));
+    assert!(romans.contains(r#"<span id="v1-16"></span><span id="v1-18"></span>1:16"#));
+    assert_eq!(romans.matches(r#"id="v8-28""#).count(), 1);
+    assert!(!refs.join("romans").exists());
+}